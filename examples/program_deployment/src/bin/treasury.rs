@@ -0,0 +1,2420 @@
+//! `treasury` — command-line client for the Treasury program.
+//!
+//! Usage:
+//!   treasury init --treasury-bin <treasury.bin> --token-bin <token.bin> --creator <ID> \
+//!       [--amm-bin <amm.bin>]
+//!   treasury create-vault [--token-definition <ID>] --admin <ID> --name <NAME> --supply <N>
+//!   treasury mint --token-definition <ID> --admin <ID> --amount <N>
+//!   treasury burn --token-definition <ID> --admin <ID> --amount <N>
+//!   treasury supply-cap --token-definition <ID> --admin <ID> [--cap <N>]
+//!   treasury send --token-definition <ID> --recipient <ID> --admin <ID> --amount <N> \
+//!       [--memo <TEXT>]
+//!   treasury send-private --token-definition <ID> --admin <ID> --amount <N> [--memo <TEXT>] \
+//!       (--recipient Private/<ID> | --recipient-npk <HEX> --recipient-ipk <HEX>)
+//!   treasury deposit --token-definition <ID> --sender <ID> --share-holding <ID> --amount <N> \
+//!       [--memo <TEXT>]
+//!   treasury deposit-private --token-definition <ID> --sender Private/<ID> \
+//!       --share-holding Private/<ID> --amount <N> [--memo <TEXT>]
+//!   treasury deposit-split --token-definition <ID> --sender <ID> --amount <N>
+//!   treasury split set --token-definition <ID> --admin <ID> --target <LABEL=BPS>... \
+//!       --dust <LABEL>
+//!   treasury split clear --token-definition <ID> --admin <ID>
+//!   treasury sweep --token-definition <ID> --admin <ID> [--from-label <LABEL>] \
+//!       [--to-label <LABEL>] --amount <N>
+//!   treasury fund-treasury --target-config <treasury.toml> --token-definition <ID> \
+//!       --admin <ID> --amount <N>
+//!   treasury redeem-from-treasury --target-config <treasury.toml> --token-definition <ID> \
+//!       --admin <ID> --shares <N>
+//!   treasury redeem --token-definition <ID> --share-holding <ID> --recipient <ID> --shares <N>
+//!   treasury initialize
+//!   treasury schedule create --token-definition <ID> --admin <ID> --recipient <ID> \
+//!       --amount <N> --interval <N> --start <T> --count <N>
+//!   treasury schedule execute --token-definition <ID> --schedule <N>
+//!   treasury schedule list --token-definition <ID>
+//!   treasury escrow create --token-definition <ID> --admin <ID> --beneficiary <ID> \
+//!       --amount <N> --arbiter <ID> --deadline <T>
+//!   treasury escrow release|refund --escrow <N>
+//!   treasury escrow reclaim --escrow <N> --admin <ID>
+//!   treasury escrow list
+//!   treasury invoice create --token-definition <ID> --admin <ID> --amount <N> [--payer <ID>] \
+//!       --due <T> --reference <TEXT>
+//!   treasury invoice pay --invoice <N> --payer <ID> [--amount <N>]
+//!   treasury invoice show --invoice <N>
+//!   treasury invoice list
+//!   treasury role assign|revoke --admin <ID> --role reviewer|guardian --account <ID>
+//!   treasury grant create --token-definition <ID> --admin <ID> --grantee <ID> \
+//!       --milestones <N,N,...>
+//!   treasury grant approve --grant <N> --milestone <N> --reviewer <ID>
+//!   treasury grant cancel --grant <N> --admin <ID>
+//!   treasury grant show --grant <N>
+//!   treasury grant list
+//!   treasury swap --from-token-definition <ID> --to-token-definition <ID> --admin <ID> \
+//!       --pool <ID> --pool-vault-a <ID> --pool-vault-b <ID> --amount-in <N> --min-amount-out <N>
+//!   treasury liquidity add --admin <ID> --pool <ID> --pool-vault-a <ID> --pool-vault-b <ID> \
+//!       --pool-lp-definition <ID> --max-amount-a <N> --max-amount-b <N> --min-amount-lp <N>
+//!   treasury liquidity remove --admin <ID> --pool <ID> --pool-vault-a <ID> --pool-vault-b <ID> \
+//!       --pool-lp-definition <ID> --lp-amount <N> --min-amount-a <N> --min-amount-b <N>
+//!   treasury vaults list
+//!   treasury vault show --token-definition <ID>
+//!   treasury vault freeze|unfreeze --token-definition <ID> --signer <ID>
+//!   treasury ledger
+//!   treasury pda [--token-definition <ID>]
+//!
+//! Program IDs are read from `treasury.toml` (written by `init`, override with
+//! `--config`). Pass `--json` for machine-readable output. Transactions are
+//! executed locally first and never submitted if the program would reject
+//! them; `--dry-run` stops after printing the resulting account changes.
+//! Errors are printed to stderr and exit with a non-zero status.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use anyhow::{Result, bail};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use nssa::AccountId;
+use nssa_core::NullifierPublicKey;
+use nssa_core::encryption::IncomingViewingPublicKey;
+use nssa_core::encryption::shared_key_derivation::Secp256k1Point;
+use nssa_core::program::ProgramId;
+use serde::Serialize;
+use treasury_examples::client::{
+    AmmPool, EscrowTerms, InvoiceTerms, ScheduleTerms, TreasuryClient, TreasuryTx,
+};
+use treasury_examples::config::{DEFAULT_CONFIG_PATH, DEFAULT_TREASURY_NAME, TreasuryConfig};
+use treasury_examples::dry_run::DryRun;
+use treasury_examples::private::{self, PrivateTx};
+use treasury_core::{
+    GrantProgress, LedgerEntry, Role, SplitConfig, SplitTarget, TokenMetadata, TreasuryMetadata,
+    VaultStatus,
+};
+use treasury_examples::query::{
+    EscrowSnapshot, GrantSnapshot, InvoiceSnapshot, ScheduleSnapshot, VaultSnapshot,
+};
+use wallet::WalletCore;
+use wallet::privacy_preserving_tx::PrivacyPreservingAccount;
+
+#[derive(Parser)]
+#[command(name = "treasury", about = "Manage a Treasury program deployment")]
+struct Cli {
+    /// Path to the program ID config file.
+    #[arg(long, global = true, default_value = DEFAULT_CONFIG_PATH)]
+    config: PathBuf,
+
+    /// Name of the treasury to act on, overriding the config's
+    /// `treasury_name`. One deployment can host many treasuries.
+    #[arg(long, global = true)]
+    treasury: Option<String>,
+
+    /// Print machine-readable JSON instead of text.
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Execute transactions locally and print the account changes without
+    /// submitting them.
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Derive program IDs from the compiled binaries and write the config file.
+    Init {
+        #[arg(long)]
+        treasury_bin: PathBuf,
+        #[arg(long)]
+        token_bin: PathBuf,
+        /// Account that creates the treasury with `initialize` and becomes
+        /// its admin. The treasury ID is derived from it and the name.
+        #[arg(long, value_parser = parse_account_id)]
+        creator: AccountId,
+        /// AMM program binary, needed for swaps and liquidity.
+        #[arg(long)]
+        amm_bin: Option<PathBuf>,
+    },
+    /// Create a new token and mint its supply into a treasury vault.
+    CreateVault {
+        /// Fresh public account to hold the token definition. Omit to use a
+        /// treasury PDA, which lets the treasury mint more later.
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: Option<AccountId>,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        /// Token name stored in the token definition, up to 6 bytes.
+        #[arg(long)]
+        name: String,
+        /// Full display name; defaults to the token name.
+        #[arg(long)]
+        display_name: Option<String>,
+        /// Ticker shown next to amounts; defaults to the token name.
+        #[arg(long)]
+        ticker: Option<String>,
+        /// Digits after the decimal point, up to 18.
+        #[arg(long, default_value_t = 0)]
+        decimals: u8,
+        /// Initial supply minted into the vault, in raw units.
+        #[arg(long)]
+        supply: u128,
+    },
+    /// Mint more of a treasury-created token into its vault (admin only).
+    Mint {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        #[arg(long)]
+        amount: u128,
+    },
+    /// Burn tokens held by a vault (admin only).
+    Burn {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        #[arg(long)]
+        amount: u128,
+    },
+    /// Set a vault's supply cap, or clear it when `--cap` is omitted (admin only).
+    SupplyCap {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        #[arg(long)]
+        cap: Option<u128>,
+    },
+    /// Send tokens from a vault to a recipient holding.
+    Send {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        #[arg(long, value_parser = parse_account_id)]
+        recipient: AccountId,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        #[arg(long)]
+        amount: u128,
+        /// Payment reference recorded in the ledger (up to 64 bytes).
+        #[arg(long, value_parser = parse_memo)]
+        memo: Option<String>,
+    },
+    /// Send tokens from a vault to a private account, as a
+    /// privacy-preserving transaction.
+    SendPrivate {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        #[command(flatten)]
+        recipient: PrivateRecipientArgs,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        #[arg(long)]
+        amount: u128,
+        /// Payment reference recorded in the ledger (up to 64 bytes).
+        #[arg(long, value_parser = parse_memo)]
+        memo: Option<String>,
+    },
+    /// Deposit tokens from a wallet holding into a vault.
+    Deposit {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Sender holding; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        sender: AccountId,
+        /// Holding that receives the vault shares; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        share_holding: AccountId,
+        #[arg(long)]
+        amount: u128,
+        /// Payment reference recorded in the ledger (up to 64 bytes).
+        #[arg(long, value_parser = parse_memo)]
+        memo: Option<String>,
+    },
+    /// Deposit tokens from a wallet holding into the labeled vaults of the
+    /// token's split.
+    DepositSplit {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Sender holding; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        sender: AccountId,
+        #[arg(long)]
+        amount: u128,
+    },
+    /// Configure how a token's split deposits are divided (admin only).
+    Split {
+        #[command(subcommand)]
+        command: SplitCommand,
+    },
+    /// Move tokens between two vaults of the same token (admin only).
+    Sweep {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        /// Label of the paying vault; omit for the main vault.
+        #[arg(long)]
+        from_label: Option<String>,
+        /// Label of the receiving vault; omit for the main vault.
+        #[arg(long)]
+        to_label: Option<String>,
+        #[arg(long)]
+        amount: u128,
+    },
+    /// Pay from a vault into another treasury deployment's vault of the same
+    /// token (admin only).
+    FundTreasury {
+        /// Config file of the receiving treasury, as written by its `init`.
+        #[arg(long)]
+        target_config: PathBuf,
+        /// Name of the receiving treasury, overriding the target config's.
+        #[arg(long)]
+        target_treasury: Option<String>,
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        #[arg(long)]
+        amount: u128,
+    },
+    /// Redeem shares that fund-treasury received from another treasury
+    /// deployment, back into the vault of the token (admin only).
+    RedeemFromTreasury {
+        /// Config file of the treasury that minted the shares, as written by
+        /// its `init`.
+        #[arg(long)]
+        target_config: PathBuf,
+        /// Name of that treasury, overriding the target config's.
+        #[arg(long)]
+        target_treasury: Option<String>,
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        #[arg(long)]
+        shares: u128,
+    },
+    /// Deposit tokens from one of the wallet's private accounts into a
+    /// vault, as a privacy-preserving transaction.
+    DepositPrivate {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// The wallet's private account paying in.
+        #[arg(long, value_parser = parse_private_account_id)]
+        sender: AccountId,
+        /// The wallet's private account that receives the vault shares. A
+        /// public one would have to sign and so reveal the depositor.
+        #[arg(long, value_parser = parse_private_account_id)]
+        share_holding: AccountId,
+        #[arg(long)]
+        amount: u128,
+        /// Payment reference recorded in the ledger (up to 64 bytes).
+        #[arg(long, value_parser = parse_memo)]
+        memo: Option<String>,
+    },
+    /// Burn vault shares and receive the proportional amount of the vault's token.
+    Redeem {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Share holding to burn from; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        share_holding: AccountId,
+        /// Token holding that receives the payout.
+        #[arg(long, value_parser = parse_account_id)]
+        recipient: AccountId,
+        #[arg(long)]
+        shares: u128,
+    },
+    /// Create the treasury, with the configured creator as its admin. The
+    /// creator must be signable by the wallet.
+    Initialize,
+    /// Manage recurring payments from a vault.
+    Schedule {
+        #[command(subcommand)]
+        command: ScheduleCommand,
+    },
+    /// Hold vault funds for a beneficiary until an arbiter decides.
+    Escrow {
+        #[command(subcommand)]
+        command: EscrowCommand,
+    },
+    /// Request payments into vaults and pay them.
+    Invoice {
+        #[command(subcommand)]
+        command: InvoiceCommand,
+    },
+    /// Assign or revoke roles (admin only).
+    Role {
+        #[command(subcommand)]
+        command: RoleCommand,
+    },
+    /// Fund and pay out milestone grants.
+    Grant {
+        #[command(subcommand)]
+        command: GrantCommand,
+    },
+    /// Pin the configured AMM program for swaps and liquidity (admin only).
+    SetAmmProgram {
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+    },
+    /// Swap between two vaults through an AMM pool (admin only).
+    Swap {
+        /// Token definition of the vault paying in.
+        #[arg(long, value_parser = parse_account_id)]
+        from_token_definition: AccountId,
+        /// Token definition of the vault receiving the output.
+        #[arg(long, value_parser = parse_account_id)]
+        to_token_definition: AccountId,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        #[command(flatten)]
+        pool: PoolArgs,
+        #[arg(long)]
+        amount_in: u128,
+        /// Least output accepted; the swap fails below this.
+        #[arg(long)]
+        min_amount_out: u128,
+    },
+    /// Provide or withdraw AMM liquidity from vaults (admin only).
+    Liquidity {
+        #[command(subcommand)]
+        command: LiquidityCommand,
+    },
+    /// Inspect the vault registry.
+    Vaults {
+        #[command(subcommand)]
+        command: VaultsCommand,
+    },
+    /// Inspect a single vault.
+    Vault {
+        #[command(subcommand)]
+        command: VaultCommand,
+    },
+    /// Set or show the treasury's name, description URI and tags.
+    Metadata {
+        #[command(subcommand)]
+        command: MetadataCommand,
+    },
+    /// Print every ledger entry, oldest first.
+    Ledger,
+    /// Print the treasury PDAs without touching the network.
+    Pda {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: Option<AccountId>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScheduleCommand {
+    /// Create a recurring payment (admin only).
+    Create {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        /// Token holding that receives the payments.
+        #[arg(long, value_parser = parse_account_id)]
+        recipient: AccountId,
+        /// Amount paid per installment.
+        #[arg(long)]
+        amount: u128,
+        /// Clock units between installments.
+        #[arg(long)]
+        interval: u64,
+        /// Time the first installment falls due.
+        #[arg(long)]
+        start: u64,
+        /// Number of installments.
+        #[arg(long)]
+        count: u64,
+    },
+    /// Pay every installment of a schedule that has fallen due.
+    Execute {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Index of the schedule within its vault.
+        #[arg(long)]
+        schedule: u64,
+    },
+    /// List a vault's payment schedules.
+    List {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+    },
+}
+
+#[derive(Subcommand)]
+enum EscrowCommand {
+    /// Move funds from a vault into a new escrow (admin only).
+    Create {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        /// Token holding paid on release.
+        #[arg(long, value_parser = parse_account_id)]
+        beneficiary: AccountId,
+        #[arg(long)]
+        amount: u128,
+        /// Account allowed to release or refund the funds.
+        #[arg(long, value_parser = parse_account_id)]
+        arbiter: AccountId,
+        /// Time after which the admin can reclaim the funds.
+        #[arg(long)]
+        deadline: u64,
+    },
+    /// Pay an escrow to its beneficiary (the arbiter signs).
+    Release {
+        #[arg(long)]
+        escrow: u64,
+    },
+    /// Return an escrow to its vault (the arbiter signs).
+    Refund {
+        #[arg(long)]
+        escrow: u64,
+    },
+    /// Return an escrow to its vault after its deadline (admin only).
+    Reclaim {
+        #[arg(long)]
+        escrow: u64,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+    },
+    /// List every escrow with its status and balance.
+    List,
+}
+
+#[derive(Subcommand)]
+enum SplitCommand {
+    /// Divide the token's split deposits between labeled vaults.
+    Set {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        /// A labeled vault and its share in basis points, as `LABEL=BPS`.
+        /// Repeat for each target; the shares must add up to 10000.
+        #[arg(long = "target", required = true, value_parser = parse_split_target)]
+        targets: Vec<SplitTarget>,
+        /// Label of the target that receives the rounding dust.
+        #[arg(long)]
+        dust: String,
+    },
+    /// Remove the token's split.
+    Clear {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+    },
+}
+
+#[derive(Subcommand)]
+enum InvoiceCommand {
+    /// Request a payment into a vault (admin only).
+    Create {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        #[arg(long)]
+        amount: u128,
+        /// Token holding that must pay. Omit to accept any payer.
+        #[arg(long, value_parser = parse_account_id)]
+        payer: Option<AccountId>,
+        /// Time by which payment is due.
+        #[arg(long)]
+        due: u64,
+        /// Reference to match the payment against (up to 64 bytes).
+        #[arg(long, value_parser = parse_memo)]
+        reference: String,
+    },
+    /// Pay an invoice in full.
+    Pay {
+        #[arg(long)]
+        invoice: u64,
+        /// Token holding paying the invoice; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        payer: AccountId,
+        /// Amount to pay. Defaults to the amount due.
+        #[arg(long)]
+        amount: Option<u128>,
+    },
+    /// Show an invoice.
+    Show {
+        #[arg(long)]
+        invoice: u64,
+    },
+    /// List every invoice with its status.
+    List,
+}
+
+#[derive(Subcommand)]
+enum LiquidityCommand {
+    /// Pay the pool's two tokens in from their vaults, receiving LP tokens
+    /// into the LP vault.
+    Add {
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        #[command(flatten)]
+        pool: PoolArgs,
+        /// The pool's LP token definition.
+        #[arg(long, value_parser = parse_account_id)]
+        pool_lp_definition: AccountId,
+        #[arg(long)]
+        max_amount_a: u128,
+        #[arg(long)]
+        max_amount_b: u128,
+        /// Least LP tokens accepted; the deposit fails below this.
+        #[arg(long)]
+        min_amount_lp: u128,
+    },
+    /// Pay LP tokens from the LP vault, receiving the pool's two tokens into
+    /// their vaults.
+    Remove {
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        #[command(flatten)]
+        pool: PoolArgs,
+        /// The pool's LP token definition.
+        #[arg(long, value_parser = parse_account_id)]
+        pool_lp_definition: AccountId,
+        #[arg(long)]
+        lp_amount: u128,
+        /// Least received into the A vault.
+        #[arg(long)]
+        min_amount_a: u128,
+        /// Least received into the B vault.
+        #[arg(long)]
+        min_amount_b: u128,
+    },
+}
+
+/// A private recipient: one of the wallet's own private accounts, or another
+/// wallet's, given by its owner's public keys.
+#[derive(Args)]
+#[group(required = true, multiple = true)]
+struct PrivateRecipientArgs {
+    /// One of the wallet's private accounts.
+    #[arg(
+        long,
+        value_parser = parse_private_account_id,
+        conflicts_with_all = ["recipient_npk", "recipient_ipk"]
+    )]
+    recipient: Option<AccountId>,
+    /// Nullifier public key of the recipient, in hex.
+    #[arg(long, value_parser = parse_npk, requires = "recipient_ipk")]
+    recipient_npk: Option<NullifierPublicKey>,
+    /// Incoming viewing public key of the recipient, in hex.
+    #[arg(long, value_parser = parse_ipk, requires = "recipient_npk")]
+    recipient_ipk: Option<IncomingViewingPublicKey>,
+}
+
+impl From<&PrivateRecipientArgs> for PrivacyPreservingAccount {
+    fn from(args: &PrivateRecipientArgs) -> Self {
+        match (&args.recipient, &args.recipient_npk, &args.recipient_ipk) {
+            (Some(account_id), _, _) => Self::PrivateOwned(*account_id),
+            (None, Some(npk), Some(ipk)) => Self::PrivateForeign {
+                npk: npk.clone(),
+                ipk: ipk.clone(),
+            },
+            _ => unreachable!("enforced by the argument group"),
+        }
+    }
+}
+
+/// An AMM pool and its token vaults, as the AMM program lists them.
+#[derive(Args)]
+struct PoolArgs {
+    #[arg(long, value_parser = parse_account_id)]
+    pool: AccountId,
+    #[arg(long, value_parser = parse_account_id)]
+    pool_vault_a: AccountId,
+    #[arg(long, value_parser = parse_account_id)]
+    pool_vault_b: AccountId,
+}
+
+impl From<&PoolArgs> for AmmPool {
+    fn from(args: &PoolArgs) -> Self {
+        Self {
+            pool: args.pool,
+            vault_a: args.pool_vault_a,
+            vault_b: args.pool_vault_b,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum RoleCommand {
+    /// Give an account a role.
+    Assign {
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        #[arg(long, value_enum)]
+        role: RoleArg,
+        #[arg(long, value_parser = parse_account_id)]
+        account: AccountId,
+    },
+    /// Take a role away from an account.
+    Revoke {
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        #[arg(long, value_enum)]
+        role: RoleArg,
+        #[arg(long, value_parser = parse_account_id)]
+        account: AccountId,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RoleArg {
+    Reviewer,
+    Guardian,
+}
+
+impl From<RoleArg> for Role {
+    fn from(role: RoleArg) -> Self {
+        match role {
+            RoleArg::Reviewer => Role::Reviewer,
+            RoleArg::Guardian => Role::Guardian,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum GrantCommand {
+    /// Fund a milestone grant from a vault (admin only).
+    Create {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        /// Token holding paid as milestones are approved.
+        #[arg(long, value_parser = parse_account_id)]
+        grantee: AccountId,
+        /// Tranche amounts in release order, comma-separated.
+        #[arg(long, value_delimiter = ',', required = true)]
+        milestones: Vec<u128>,
+    },
+    /// Release the next milestone to the grantee (reviewer only).
+    Approve {
+        #[arg(long)]
+        grant: u64,
+        #[arg(long)]
+        milestone: u32,
+        /// Reviewer account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        reviewer: AccountId,
+    },
+    /// Cancel the pending milestones and return their funds (admin only).
+    Cancel {
+        #[arg(long)]
+        grant: u64,
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+    },
+    /// Show a grant's milestones and progress.
+    Show {
+        #[arg(long)]
+        grant: u64,
+    },
+    /// List every grant with its progress.
+    List,
+}
+
+#[derive(Subcommand)]
+enum VaultsCommand {
+    /// List every vault registered in the treasury state, with balances.
+    List,
+}
+
+#[derive(Subcommand)]
+enum VaultCommand {
+    /// Show a vault's holding account and balance.
+    Show {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+    },
+    /// Reject all funds moving into or out of a vault (admin or guardian).
+    Freeze {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Admin or guardian account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        signer: AccountId,
+    },
+    /// Let funds move through a frozen vault again (admin or guardian).
+    Unfreeze {
+        #[arg(long, value_parser = parse_account_id)]
+        token_definition: AccountId,
+        /// Admin or guardian account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        signer: AccountId,
+    },
+}
+
+#[derive(Subcommand)]
+enum MetadataCommand {
+    /// Set the treasury's metadata, replacing any earlier one (admin only).
+    Set {
+        /// Admin account; must be signable by the wallet.
+        #[arg(long, value_parser = parse_account_id)]
+        admin: AccountId,
+        /// Display name, up to 64 bytes.
+        #[arg(long)]
+        name: String,
+        /// Link to a longer description, up to 200 bytes.
+        #[arg(long, default_value = "")]
+        uri: String,
+        /// Keywords such as a contact handle, comma-separated; up to 8 of up
+        /// to 32 bytes each.
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+    },
+    /// Show the treasury's metadata.
+    Show,
+}
+
+/// Accepts both a bare account ID and the wallet's `Public/<ID>` form.
+fn parse_account_id(value: &str) -> Result<AccountId, String> {
+    let value = value.strip_prefix("Public/").unwrap_or(value);
+    value
+        .parse()
+        .map_err(|err| format!("invalid account ID {value:?}: {err:?}"))
+}
+
+fn parse_memo(value: &str) -> Result<String, String> {
+    treasury_core::validate_memo(Some(value)).map_err(|err| err.to_string())?;
+    Ok(value.to_string())
+}
+
+fn parse_split_target(value: &str) -> Result<SplitTarget, String> {
+    let Some((label, bps)) = value.split_once('=') else {
+        return Err(format!("expected LABEL=BPS, got {value:?}"));
+    };
+    let bps = bps
+        .parse()
+        .map_err(|err| format!("invalid basis points {bps:?}: {err}"))?;
+    Ok(SplitTarget {
+        label: label.to_string(),
+        bps,
+    })
+}
+
+fn parse_private_account_id(value: &str) -> Result<AccountId, String> {
+    let value = value.strip_prefix("Private/").unwrap_or(value);
+    value
+        .parse()
+        .map_err(|err| format!("invalid account ID {value:?}: {err:?}"))
+}
+
+fn parse_npk(value: &str) -> Result<NullifierPublicKey, String> {
+    let bytes: [u8; 32] = parse_hex(value)?
+        .try_into()
+        .map_err(|_| "nullifier public key must be 32 bytes".to_string())?;
+    Ok(NullifierPublicKey(bytes))
+}
+
+fn parse_ipk(value: &str) -> Result<IncomingViewingPublicKey, String> {
+    let bytes = parse_hex(value)?;
+    if bytes.len() != 33 {
+        return Err("incoming viewing public key must be 33 bytes".to_string());
+    }
+    Ok(Secp256k1Point(bytes))
+}
+
+fn parse_hex(value: &str) -> Result<Vec<u8>, String> {
+    hex::decode(value).map_err(|err| format!("invalid hex {value:?}: {err}"))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            if cli.json {
+                eprintln!("{}", serde_json::json!({ "error": format!("{err:#}") }));
+            } else {
+                eprintln!("error: {err:#}");
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: &Cli) -> Result<()> {
+    if let Command::Init {
+        treasury_bin,
+        token_bin,
+        creator,
+        amm_bin,
+    } = &cli.command
+    {
+        let config = TreasuryConfig::from_binaries(
+            treasury_bin,
+            token_bin,
+            amm_bin.as_deref(),
+            *creator,
+            cli.treasury.as_deref().unwrap_or(DEFAULT_TREASURY_NAME),
+        )?;
+        config.save(&cli.config)?;
+        return emit(
+            cli.json,
+            &InitReport {
+                config_path: cli.config.display().to_string(),
+                treasury_program_id: config.treasury_program_id,
+                treasury_creator: config.treasury_creator.to_string(),
+                treasury_name: config.treasury_name,
+                token_program_id: config.token_program_id,
+                amm_program_id: config.amm_program_id,
+            },
+        );
+    }
+
+    let mut config = TreasuryConfig::load(&cli.config)?;
+    if let Some(name) = &cli.treasury {
+        config.treasury_name.clone_from(name);
+    }
+
+    if let Command::Pda { token_definition } = &cli.command {
+        return emit(cli.json, &PdaReport::new(&config, token_definition.as_ref()));
+    }
+
+    let wallet = WalletCore::from_env()
+        .map_err(|err| anyhow::anyhow!("failed to load wallet: {err:?}"))?;
+    let client = TreasuryClient::new(wallet, config);
+
+    match &cli.command {
+        Command::Init { .. } | Command::Pda { .. } => unreachable!("handled above"),
+        Command::CreateVault {
+            token_definition,
+            admin,
+            name,
+            display_name,
+            ticker,
+            decimals,
+            supply,
+        } => {
+            let token_definition = match token_definition {
+                Some(token_definition) => *token_definition,
+                None => client.next_token_definition_id().await?,
+            };
+            let metadata = TokenMetadata {
+                display_name: display_name.clone().unwrap_or_else(|| name.clone()),
+                ticker: ticker.clone().unwrap_or_else(|| name.clone()),
+                decimals: *decimals,
+            };
+            let tx = client.create_vault_tx(token_definition, *admin, name, &metadata, *supply);
+            let report = TxReport {
+                instruction: "CreateVault",
+                vault_holding_id: Some(client.vault_holding_id(&token_definition).to_string()),
+                counterparty_id: token_definition.to_string(),
+                amount: Some(*supply),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Mint {
+            token_definition,
+            admin,
+            amount,
+        } => {
+            let tx = client.mint_more_tx(*token_definition, *admin, *amount);
+            let report = TxReport {
+                instruction: "MintMore",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: token_definition.to_string(),
+                amount: Some(*amount),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Burn {
+            token_definition,
+            admin,
+            amount,
+        } => {
+            let tx = client.burn_tx(*token_definition, *admin, *amount);
+            let report = TxReport {
+                instruction: "Burn",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: token_definition.to_string(),
+                amount: Some(*amount),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::SupplyCap {
+            token_definition,
+            admin,
+            cap,
+        } => {
+            let tx = client.set_supply_cap_tx(*token_definition, *admin, *cap);
+            let report = TxReport {
+                instruction: "SetSupplyCap",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: token_definition.to_string(),
+                amount: *cap,
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Send {
+            token_definition,
+            recipient,
+            admin,
+            amount,
+            memo,
+        } => {
+            let tx = client.send_tx(*token_definition, *recipient, *admin, *amount, memo.clone());
+            let report = TxReport {
+                instruction: "Send",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: recipient.to_string(),
+                amount: Some(*amount),
+                memo: memo.clone(),
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::SendPrivate {
+            token_definition,
+            recipient,
+            admin,
+            amount,
+            memo,
+        } => {
+            let recipient = PrivacyPreservingAccount::from(recipient);
+            let tx =
+                client.private_send_tx(*token_definition, recipient, *admin, *amount, memo.clone());
+            let report = TxReport {
+                instruction: "Send (private)",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: private::account_id(&tx.accounts[2]).to_string(),
+                amount: Some(*amount),
+                memo: memo.clone(),
+            };
+            execute_private(cli, &client, &tx, report).await
+        }
+        Command::Deposit {
+            token_definition,
+            sender,
+            share_holding,
+            amount,
+            memo,
+        } => {
+            let tx = client.deposit_tx(
+                *token_definition,
+                *sender,
+                *share_holding,
+                *amount,
+                memo.clone(),
+            );
+            let report = TxReport {
+                instruction: "Deposit",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: sender.to_string(),
+                amount: Some(*amount),
+                memo: memo.clone(),
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::DepositSplit {
+            token_definition,
+            sender,
+            amount,
+        } => {
+            let tx = client
+                .deposit_split_tx(*token_definition, *sender, *amount)
+                .await?;
+            let report = TxReport {
+                instruction: "DepositSplit",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: sender.to_string(),
+                amount: Some(*amount),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Split {
+            command:
+                SplitCommand::Set {
+                    token_definition,
+                    admin,
+                    targets,
+                    dust,
+                },
+        } => {
+            let split = SplitConfig {
+                targets: targets.clone(),
+                dust_label: dust.clone(),
+            };
+            let tx = client.set_split_tx(*token_definition, *admin, Some(split));
+            let report = TxReport {
+                instruction: "SetSplit",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: admin.to_string(),
+                amount: None,
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Split {
+            command: SplitCommand::Clear { token_definition, admin },
+        } => {
+            let tx = client.set_split_tx(*token_definition, *admin, None);
+            let report = TxReport {
+                instruction: "SetSplit",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: admin.to_string(),
+                amount: None,
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Sweep {
+            token_definition,
+            admin,
+            from_label,
+            to_label,
+            amount,
+        } => {
+            let tx = client.internal_transfer_tx(
+                *token_definition,
+                from_label.clone(),
+                to_label.clone(),
+                *admin,
+                *amount,
+            );
+            let report = TxReport {
+                instruction: "InternalTransfer",
+                vault_holding_id: Some(
+                    client
+                        .vault_id(token_definition, from_label.as_deref())
+                        .to_string(),
+                ),
+                counterparty_id: client
+                    .vault_id(token_definition, to_label.as_deref())
+                    .to_string(),
+                amount: Some(*amount),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::FundTreasury {
+            target_config,
+            target_treasury,
+            token_definition,
+            admin,
+            amount,
+        } => {
+            let target = load_target(&client, target_config, target_treasury.as_deref())?;
+            let target_program_id = target.treasury_program_id;
+            let target_id = target.treasury_id();
+            let tx = client
+                .fund_treasury_tx(
+                    target_program_id,
+                    target_id,
+                    *token_definition,
+                    *admin,
+                    *amount,
+                )
+                .await?;
+            let report = TxReport {
+                instruction: "FundTreasury",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: treasury_core::compute_vault_holding_pda(
+                    &target_program_id,
+                    &target_id,
+                    token_definition,
+                )
+                .to_string(),
+                amount: Some(*amount),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::RedeemFromTreasury {
+            target_config,
+            target_treasury,
+            token_definition,
+            admin,
+            shares,
+        } => {
+            let target = load_target(&client, target_config, target_treasury.as_deref())?;
+            let target_program_id = target.treasury_program_id;
+            let target_id = target.treasury_id();
+            let tx = client
+                .redeem_from_treasury_tx(
+                    target_program_id,
+                    target_id,
+                    *token_definition,
+                    *admin,
+                    *shares,
+                )
+                .await?;
+            let report = TxReport {
+                instruction: "RedeemFromTreasury",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: treasury_core::compute_vault_holding_pda(
+                    &target_program_id,
+                    &target_id,
+                    token_definition,
+                )
+                .to_string(),
+                amount: Some(*shares),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::DepositPrivate {
+            token_definition,
+            sender,
+            share_holding,
+            amount,
+            memo,
+        } => {
+            let tx = client.private_deposit_tx(
+                *token_definition,
+                *sender,
+                *share_holding,
+                *amount,
+                memo.clone(),
+            );
+            let report = TxReport {
+                instruction: "Deposit (private)",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: sender.to_string(),
+                amount: Some(*amount),
+                memo: memo.clone(),
+            };
+            execute_private(cli, &client, &tx, report).await
+        }
+        Command::Redeem {
+            token_definition,
+            share_holding,
+            recipient,
+            shares,
+        } => {
+            let tx = client.redeem_tx(*token_definition, *share_holding, *recipient, *shares);
+            let report = TxReport {
+                instruction: "Redeem",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: recipient.to_string(),
+                amount: Some(*shares),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Initialize => {
+            let tx = client.initialize_tx();
+            let report = TxReport {
+                instruction: "Initialize",
+                vault_holding_id: None,
+                counterparty_id: client.config().treasury_creator.to_string(),
+                amount: None,
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Schedule {
+            command:
+                ScheduleCommand::Create {
+                    token_definition,
+                    admin,
+                    recipient,
+                    amount,
+                    interval,
+                    start,
+                    count,
+                },
+        } => {
+            let terms = ScheduleTerms {
+                recipient: *recipient,
+                amount: *amount,
+                interval: *interval,
+                start: *start,
+                count: *count,
+            };
+            let tx = client
+                .create_schedule_tx(*token_definition, *admin, &terms)
+                .await?;
+            let report = TxReport {
+                instruction: "CreateSchedule",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: recipient.to_string(),
+                amount: Some(*amount),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Schedule {
+            command:
+                ScheduleCommand::Execute {
+                    token_definition,
+                    schedule,
+                },
+        } => {
+            let tx = client.execute_due_tx(*token_definition, *schedule).await?;
+            let snapshot = client.schedule(*token_definition, *schedule).await?;
+            let now = client.clock().await?;
+            let pending = snapshot
+                .schedule
+                .due_count(now)
+                .saturating_sub(snapshot.schedule.paid);
+            let report = TxReport {
+                instruction: "ExecuteDue",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: snapshot.schedule.recipient.to_string(),
+                amount: snapshot.schedule.amount.checked_mul(u128::from(pending)),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Schedule {
+            command: ScheduleCommand::List { token_definition },
+        } => {
+            let now = client.clock().await.ok();
+            let schedules = client.schedules(*token_definition).await?;
+            emit(
+                cli.json,
+                &ScheduleList {
+                    now,
+                    schedules: schedules
+                        .iter()
+                        .map(|schedule| ScheduleEntry::new(schedule, now))
+                        .collect(),
+                },
+            )
+        }
+        Command::Escrow {
+            command:
+                EscrowCommand::Create {
+                    token_definition,
+                    admin,
+                    beneficiary,
+                    amount,
+                    arbiter,
+                    deadline,
+                },
+        } => {
+            let terms = EscrowTerms {
+                beneficiary: *beneficiary,
+                amount: *amount,
+                arbiter: *arbiter,
+                deadline: *deadline,
+            };
+            let tx = client
+                .create_escrow_tx(*token_definition, *admin, &terms)
+                .await?;
+            let report = TxReport {
+                instruction: "CreateEscrow",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: beneficiary.to_string(),
+                amount: Some(*amount),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Escrow {
+            command: EscrowCommand::Release { escrow },
+        } => {
+            let tx = client.release_escrow_tx(*escrow).await?;
+            let snapshot = client.escrow(*escrow).await?;
+            let report = TxReport {
+                instruction: "Release",
+                vault_holding_id: Some(snapshot.escrow.vault_id.to_string()),
+                counterparty_id: snapshot.escrow.beneficiary.to_string(),
+                amount: Some(snapshot.escrow.amount),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Escrow {
+            command: EscrowCommand::Refund { escrow },
+        } => {
+            let tx = client.refund_escrow_tx(*escrow).await?;
+            let snapshot = client.escrow(*escrow).await?;
+            let report = TxReport {
+                instruction: "Refund",
+                vault_holding_id: Some(snapshot.escrow.vault_id.to_string()),
+                counterparty_id: snapshot.holding_id.to_string(),
+                amount: Some(snapshot.escrow.amount),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Escrow {
+            command: EscrowCommand::Reclaim { escrow, admin },
+        } => {
+            let tx = client.reclaim_escrow_tx(*escrow, *admin).await?;
+            let snapshot = client.escrow(*escrow).await?;
+            let report = TxReport {
+                instruction: "Reclaim",
+                vault_holding_id: Some(snapshot.escrow.vault_id.to_string()),
+                counterparty_id: snapshot.holding_id.to_string(),
+                amount: Some(snapshot.escrow.amount),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Escrow {
+            command: EscrowCommand::List,
+        } => {
+            let escrows = client.escrows().await?;
+            emit(
+                cli.json,
+                &EscrowList {
+                    escrows: escrows.iter().map(EscrowEntry::from).collect(),
+                },
+            )
+        }
+        Command::Invoice {
+            command:
+                InvoiceCommand::Create {
+                    token_definition,
+                    admin,
+                    amount,
+                    payer,
+                    due,
+                    reference,
+                },
+        } => {
+            let terms = InvoiceTerms {
+                amount: *amount,
+                payer: *payer,
+                due: *due,
+                reference: reference.clone(),
+            };
+            let tx = client
+                .create_invoice_tx(*token_definition, *admin, &terms)
+                .await?;
+            let report = TxReport {
+                instruction: "CreateInvoice",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: payer.unwrap_or_default().to_string(),
+                amount: Some(*amount),
+                memo: Some(reference.clone()),
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Invoice {
+            command: InvoiceCommand::Pay { invoice, payer, amount },
+        } => {
+            let snapshot = client.invoice(*invoice).await?;
+            let amount = amount.unwrap_or(snapshot.invoice.amount);
+            let tx = client.pay_invoice_tx(*invoice, *payer, amount).await?;
+            let report = TxReport {
+                instruction: "PayInvoice",
+                vault_holding_id: Some(snapshot.invoice.vault_id.to_string()),
+                counterparty_id: payer.to_string(),
+                amount: Some(amount),
+                memo: Some(snapshot.invoice.reference),
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Invoice {
+            command: InvoiceCommand::Show { invoice },
+        } => {
+            let snapshot = client.invoice(*invoice).await?;
+            emit(cli.json, &InvoiceEntry::from(&snapshot))
+        }
+        Command::Invoice {
+            command: InvoiceCommand::List,
+        } => {
+            let invoices = client.invoices().await?;
+            emit(
+                cli.json,
+                &InvoiceList {
+                    invoices: invoices.iter().map(InvoiceEntry::from).collect(),
+                },
+            )
+        }
+        Command::Role {
+            command: RoleCommand::Assign { admin, role, account },
+        } => {
+            let tx = client.assign_role_tx(*admin, (*role).into(), *account);
+            let report = TxReport {
+                instruction: "AssignRole",
+                vault_holding_id: None,
+                counterparty_id: account.to_string(),
+                amount: None,
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Role {
+            command: RoleCommand::Revoke { admin, role, account },
+        } => {
+            let tx = client.revoke_role_tx(*admin, (*role).into(), *account);
+            let report = TxReport {
+                instruction: "RevokeRole",
+                vault_holding_id: None,
+                counterparty_id: account.to_string(),
+                amount: None,
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Grant {
+            command:
+                GrantCommand::Create {
+                    token_definition,
+                    admin,
+                    grantee,
+                    milestones,
+                },
+        } => {
+            let total = milestones
+                .iter()
+                .try_fold(0u128, |total, amount| total.checked_add(*amount));
+            let tx = client
+                .create_grant_tx(*token_definition, *admin, *grantee, milestones.clone())
+                .await?;
+            let report = TxReport {
+                instruction: "CreateGrant",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: grantee.to_string(),
+                amount: total,
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Grant {
+            command:
+                GrantCommand::Approve {
+                    grant,
+                    milestone,
+                    reviewer,
+                },
+        } => {
+            let tx = client
+                .approve_milestone_tx(*grant, *milestone, *reviewer)
+                .await?;
+            let snapshot = client.grant(*grant).await?;
+            let report = TxReport {
+                instruction: "ApproveMilestone",
+                vault_holding_id: None,
+                counterparty_id: snapshot.grant.grantee.to_string(),
+                amount: snapshot
+                    .grant
+                    .milestones
+                    .get(*milestone as usize)
+                    .map(|milestone| milestone.amount),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Grant {
+            command: GrantCommand::Cancel { grant, admin },
+        } => {
+            let tx = client.cancel_grant_tx(*grant, *admin).await?;
+            let snapshot = client.grant(*grant).await?;
+            let report = TxReport {
+                instruction: "CancelGrant",
+                vault_holding_id: Some(snapshot.grant.vault_id.to_string()),
+                counterparty_id: snapshot.holding_id.to_string(),
+                amount: Some(snapshot.progress.pending),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Grant {
+            command: GrantCommand::Show { grant },
+        } => {
+            let snapshot = client.grant(*grant).await?;
+            emit(cli.json, &GrantEntry::from(&snapshot))
+        }
+        Command::Grant {
+            command: GrantCommand::List,
+        } => {
+            let grants = client.grants().await?;
+            emit(
+                cli.json,
+                &GrantList {
+                    grants: grants.iter().map(GrantEntry::from).collect(),
+                },
+            )
+        }
+        Command::SetAmmProgram { admin } => {
+            let tx = client.set_amm_program_tx(*admin)?;
+            let report = TxReport {
+                instruction: "SetAmmProgram",
+                vault_holding_id: None,
+                counterparty_id: admin.to_string(),
+                amount: None,
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Swap {
+            from_token_definition,
+            to_token_definition,
+            admin,
+            pool,
+            amount_in,
+            min_amount_out,
+        } => {
+            let tx = client.swap_tx(
+                *from_token_definition,
+                *to_token_definition,
+                &AmmPool::from(pool),
+                *admin,
+                *amount_in,
+                *min_amount_out,
+            );
+            let report = TxReport {
+                instruction: "Swap",
+                vault_holding_id: Some(client.vault_holding_id(from_token_definition).to_string()),
+                counterparty_id: client.vault_holding_id(to_token_definition).to_string(),
+                amount: Some(*amount_in),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Liquidity {
+            command:
+                LiquidityCommand::Add {
+                    admin,
+                    pool,
+                    pool_lp_definition,
+                    max_amount_a,
+                    max_amount_b,
+                    min_amount_lp,
+                },
+        } => {
+            let tx = client
+                .add_liquidity_tx(
+                    &AmmPool::from(pool),
+                    *pool_lp_definition,
+                    *admin,
+                    *max_amount_a,
+                    *max_amount_b,
+                    *min_amount_lp,
+                )
+                .await?;
+            let report = TxReport {
+                instruction: "AddLiquidity",
+                vault_holding_id: Some(client.vault_holding_id(pool_lp_definition).to_string()),
+                counterparty_id: pool.pool.to_string(),
+                amount: None,
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Liquidity {
+            command:
+                LiquidityCommand::Remove {
+                    admin,
+                    pool,
+                    pool_lp_definition,
+                    lp_amount,
+                    min_amount_a,
+                    min_amount_b,
+                },
+        } => {
+            let tx = client
+                .remove_liquidity_tx(
+                    &AmmPool::from(pool),
+                    *pool_lp_definition,
+                    *admin,
+                    *lp_amount,
+                    *min_amount_a,
+                    *min_amount_b,
+                )
+                .await?;
+            let report = TxReport {
+                instruction: "RemoveLiquidity",
+                vault_holding_id: Some(client.vault_holding_id(pool_lp_definition).to_string()),
+                counterparty_id: pool.pool.to_string(),
+                amount: Some(*lp_amount),
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Vaults {
+            command: VaultsCommand::List,
+        } => {
+            let snapshot = client.snapshot().await?;
+            emit(
+                cli.json,
+                &VaultList {
+                    vault_count: snapshot.state.vault_count,
+                    vaults: snapshot.vaults.into_iter().map(VaultView).collect(),
+                },
+            )
+        }
+        Command::Vault {
+            command: VaultCommand::Show { token_definition },
+        } => {
+            let vault = client.vault_snapshot(*token_definition).await?;
+            emit(cli.json, &VaultView(vault))
+        }
+        Command::Vault {
+            command: VaultCommand::Freeze { token_definition, signer },
+        } => {
+            let tx = client.set_vault_frozen_tx(*token_definition, *signer, true);
+            let report = TxReport {
+                instruction: "FreezeVault",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: signer.to_string(),
+                amount: None,
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Vault {
+            command: VaultCommand::Unfreeze { token_definition, signer },
+        } => {
+            let tx = client.set_vault_frozen_tx(*token_definition, *signer, false);
+            let report = TxReport {
+                instruction: "UnfreezeVault",
+                vault_holding_id: Some(client.vault_holding_id(token_definition).to_string()),
+                counterparty_id: signer.to_string(),
+                amount: None,
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Metadata {
+            command:
+                MetadataCommand::Set {
+                    admin,
+                    name,
+                    uri,
+                    tags,
+                },
+        } => {
+            let metadata = TreasuryMetadata {
+                name: name.clone(),
+                uri: uri.clone(),
+                tags: tags.clone(),
+            };
+            let tx = client.set_metadata_tx(*admin, &metadata);
+            let report = TxReport {
+                instruction: "SetMetadata",
+                vault_holding_id: None,
+                counterparty_id: client.metadata_id().to_string(),
+                amount: None,
+                memo: None,
+            };
+            execute(cli, &client, &tx, report).await
+        }
+        Command::Metadata {
+            command: MetadataCommand::Show,
+        } => {
+            let metadata = client.metadata().await?;
+            emit(
+                cli.json,
+                &MetadataEntry {
+                    metadata_id: client.metadata_id().to_string(),
+                    metadata,
+                },
+            )
+        }
+        Command::Ledger => {
+            let entries = client.ledger().await?;
+            emit(
+                cli.json,
+                &LedgerList {
+                    entries: entries.iter().map(LedgerRow::from).collect(),
+                },
+            )
+        }
+    }
+}
+
+/// Dry-run the transaction, and submit it unless `--dry-run` was given.
+async fn execute(
+    cli: &Cli,
+    client: &TreasuryClient,
+    tx: &TreasuryTx,
+    report: TxReport,
+) -> Result<()> {
+    if cli.dry_run {
+        let simulated = client.dry_run(tx).await?;
+        return emit(cli.json, &DryRunReport::new(report, &simulated));
+    }
+    let simulated = client.submit(tx).await?;
+    emit(
+        cli.json,
+        &SubmittedReport {
+            transaction: report,
+            changes: DryRunReport::changes(&simulated),
+        },
+    )
+}
+
+/// Dry-run a private transaction, and prove and submit it unless
+/// `--dry-run` was given.
+async fn execute_private(
+    cli: &Cli,
+    client: &TreasuryClient,
+    tx: &PrivateTx,
+    report: TxReport,
+) -> Result<()> {
+    if cli.dry_run {
+        let simulated = client.dry_run_private(tx).await?;
+        return emit(cli.json, &DryRunReport::new(report, &simulated));
+    }
+    let simulated = client.submit_private(tx).await?;
+    emit(
+        cli.json,
+        &SubmittedReport {
+            transaction: report,
+            changes: DryRunReport::changes(&simulated),
+        },
+    )
+}
+
+/// Load the config of another treasury to pay into or redeem from, checking
+/// that it chains to the same Token program.
+fn load_target(
+    client: &TreasuryClient,
+    target_config: &Path,
+    target_treasury: Option<&str>,
+) -> Result<TreasuryConfig> {
+    let mut target = TreasuryConfig::load(target_config)?;
+    if let Some(name) = target_treasury {
+        target.treasury_name = name.to_string();
+    }
+    if target.token_program_id != client.config().token_program_id {
+        bail!("the target treasury chains to a different token program");
+    }
+    Ok(target)
+}
+
+fn emit<T: Serialize + fmt::Display>(json: bool, report: &T) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(report)?);
+    } else {
+        println!("{report}");
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct InitReport {
+    config_path: String,
+    treasury_program_id: ProgramId,
+    treasury_creator: String,
+    treasury_name: String,
+    token_program_id: ProgramId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    amm_program_id: Option<ProgramId>,
+}
+
+impl fmt::Display for InitReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Wrote {}", self.config_path)?;
+        writeln!(f, "Treasury program ID:    {:?}", self.treasury_program_id)?;
+        writeln!(f, "Treasury creator:       {}", self.treasury_creator)?;
+        writeln!(f, "Treasury name:          {}", self.treasury_name)?;
+        write!(f, "Token program ID:       {:?}", self.token_program_id)?;
+        if let Some(amm_program_id) = &self.amm_program_id {
+            write!(f, "\nAMM program ID:         {amm_program_id:?}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct PdaReport {
+    treasury_program_id: ProgramId,
+    treasury_creator: String,
+    treasury_name: String,
+    treasury_state_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_definition_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vault_holding_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    share_definition_id: Option<String>,
+}
+
+impl PdaReport {
+    fn new(config: &TreasuryConfig, token_definition_id: Option<&AccountId>) -> Self {
+        let program_id = &config.treasury_program_id;
+        let treasury = &config.treasury_id();
+        Self {
+            treasury_program_id: *program_id,
+            treasury_creator: config.treasury_creator.to_string(),
+            treasury_name: config.treasury_name.clone(),
+            treasury_state_id: treasury_core::compute_treasury_state_pda(program_id, treasury)
+                .to_string(),
+            token_definition_id: token_definition_id.map(ToString::to_string),
+            vault_holding_id: token_definition_id.map(|def| {
+                treasury_core::compute_vault_holding_pda(program_id, treasury, def).to_string()
+            }),
+            share_definition_id: token_definition_id.map(|def| {
+                treasury_core::compute_share_definition_pda(program_id, treasury, def).to_string()
+            }),
+        }
+    }
+}
+
+impl fmt::Display for PdaReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Treasury program ID:    {:?}", self.treasury_program_id)?;
+        writeln!(f, "Treasury creator:       {}", self.treasury_creator)?;
+        writeln!(f, "Treasury name:          {}", self.treasury_name)?;
+        write!(f, "Treasury state PDA:     {}", self.treasury_state_id)?;
+        if let (Some(def), Some(vault), Some(shares)) = (
+            &self.token_definition_id,
+            &self.vault_holding_id,
+            &self.share_definition_id,
+        ) {
+            write!(f, "\nToken definition:       {def}")?;
+            write!(f, "\nVault holding PDA:      {vault}")?;
+            write!(f, "\nShare definition PDA:   {shares}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct TxReport {
+    instruction: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vault_holding_id: Option<String>,
+    counterparty_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    amount: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<String>,
+}
+
+impl fmt::Display for TxReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "✅ {} transaction submitted!", self.instruction)?;
+        if let Some(vault) = &self.vault_holding_id {
+            write!(f, "\n   Vault holding PDA:  {vault}")?;
+        }
+        write!(f, "\n   Counterparty:       {}", self.counterparty_id)?;
+        if let Some(amount) = self.amount {
+            write!(f, "\n   Amount:             {amount}")?;
+        }
+        if let Some(memo) = &self.memo {
+            write!(f, "\n   Memo:               {memo}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct AccountChangeEntry {
+    account_id: String,
+    balance_before: u128,
+    balance_after: u128,
+    token_balance_before: Option<u128>,
+    token_balance_after: Option<u128>,
+    data_changed: bool,
+}
+
+#[derive(Serialize)]
+struct DryRunReport {
+    transaction: TxReport,
+    changes: Vec<AccountChangeEntry>,
+    /// Programs whose chained calls were not simulated.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unsimulated_programs: Vec<ProgramId>,
+    #[serde(skip)]
+    summary: String,
+}
+
+impl DryRunReport {
+    fn new(transaction: TxReport, simulated: &DryRun) -> Self {
+        Self {
+            transaction,
+            changes: Self::changes(simulated),
+            unsimulated_programs: simulated.unsimulated.clone(),
+            summary: simulated.to_string(),
+        }
+    }
+
+    fn changes(simulated: &DryRun) -> Vec<AccountChangeEntry> {
+        simulated
+            .changed()
+            .map(|change| AccountChangeEntry {
+                account_id: change.account_id.to_string(),
+                balance_before: change.before.balance,
+                balance_after: change.after.balance,
+                token_balance_before: change.token_balance_before(),
+                token_balance_after: change.token_balance_after(),
+                data_changed: change.before.data != change.after.data,
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for DryRunReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} would succeed (not submitted)", self.transaction.instruction)?;
+        write!(f, "{}", self.summary)
+    }
+}
+
+#[derive(Serialize)]
+struct SubmittedReport {
+    transaction: TxReport,
+    changes: Vec<AccountChangeEntry>,
+}
+
+impl fmt::Display for SubmittedReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.transaction)
+    }
+}
+
+/// A vault as `vault show` and `vaults list` print it; the JSON form is the
+/// snapshot itself.
+#[derive(Serialize)]
+#[serde(transparent)]
+struct VaultView(VaultSnapshot);
+
+impl fmt::Display for VaultView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self(vault) = self;
+        let record = &vault.record;
+        writeln!(f, "Token definition:       {}", record.token_definition_id)?;
+        writeln!(f, "Vault holding PDA:      {}", record.holding_id)?;
+        writeln!(f, "Registered:             {}", vault.registered)?;
+        writeln!(f, "Frozen:                 {}", record.status == VaultStatus::Frozen)?;
+        if let Some(metadata) = &record.metadata {
+            writeln!(
+                f,
+                "Token:                  {} ({}, {} decimals)",
+                metadata.display_name, metadata.ticker, metadata.decimals
+            )?;
+        }
+        match (vault.balance, &record.metadata) {
+            (Some(balance), Some(metadata)) => writeln!(
+                f,
+                "Balance:                {} {} ({balance} raw)",
+                metadata.format_amount(balance),
+                metadata.ticker
+            )?,
+            (Some(balance), None) => writeln!(f, "Balance:                {balance}")?,
+            (None, _) => writeln!(f, "Balance:                (uninitialized)")?,
+        }
+        writeln!(f, "Share definition PDA:   {}", vault.share_definition_id)?;
+        writeln!(f, "Depositor shares:       {}", record.share_supply)?;
+        writeln!(f, "Treasury shares:        {}", record.treasury_shares)?;
+        match record.supply_cap {
+            Some(cap) => writeln!(f, "Supply cap:             {cap}")?,
+            None => writeln!(f, "Supply cap:             (none)")?,
+        }
+        writeln!(f, "Minted by treasury:     {}", record.minted)?;
+        writeln!(f, "Burned by treasury:     {}", record.burned)?;
+        writeln!(
+            f,
+            "Deposited:              {} ({} deposit(s))",
+            record.deposited, record.deposit_count
+        )?;
+        write!(f, "Sent:                   {} ({} send(s))", record.sent, record.send_count)?;
+        if let Some(split) = &record.split {
+            write!(f, "\nSplit (dust to {}):", split.dust_label)?;
+            for (target, labeled) in split.targets.iter().zip(&vault.split_vaults) {
+                write!(
+                    f,
+                    "\n  {:<20} {:>5} bps  {}  balance {}",
+                    target.label,
+                    target.bps,
+                    labeled.holding_id,
+                    labeled
+                        .balance
+                        .map_or("(uninitialized)".to_string(), |balance| balance.to_string())
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct VaultList {
+    vault_count: u64,
+    vaults: Vec<VaultView>,
+}
+
+impl fmt::Display for VaultList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} vault(s)", self.vault_count)?;
+        for vault in &self.vaults {
+            write!(f, "\n\n{vault}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct ScheduleEntry {
+    index: u64,
+    schedule_id: String,
+    recipient: String,
+    amount: u128,
+    interval: u64,
+    start: u64,
+    count: u64,
+    paid: u64,
+    next_due: Option<u64>,
+    /// Installments payable now, if the clock could be read.
+    due_now: Option<u64>,
+}
+
+impl ScheduleEntry {
+    fn new(snapshot: &ScheduleSnapshot, now: Option<u64>) -> Self {
+        let schedule = &snapshot.schedule;
+        Self {
+            index: snapshot.index,
+            schedule_id: snapshot.schedule_id.to_string(),
+            recipient: schedule.recipient.to_string(),
+            amount: schedule.amount,
+            interval: schedule.interval,
+            start: schedule.start,
+            count: schedule.count,
+            paid: schedule.paid,
+            next_due: schedule.next_due(),
+            due_now: now.map(|now| schedule.due_count(now).saturating_sub(schedule.paid)),
+        }
+    }
+}
+
+impl fmt::Display for ScheduleEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Schedule {}:             {}", self.index, self.schedule_id)?;
+        writeln!(f, "Recipient:              {}", self.recipient)?;
+        writeln!(f, "Amount:                 {}", self.amount)?;
+        writeln!(f, "Every:                  {} from {}", self.interval, self.start)?;
+        write!(f, "Paid:                   {} of {}", self.paid, self.count)?;
+        match self.next_due {
+            Some(next_due) => write!(f, "\nNext due:               {next_due}")?,
+            None => write!(f, "\nNext due:               (complete)")?,
+        }
+        if let Some(due_now) = self.due_now {
+            write!(f, "\nDue now:                {due_now}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct ScheduleList {
+    now: Option<u64>,
+    schedules: Vec<ScheduleEntry>,
+}
+
+impl fmt::Display for ScheduleList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} schedule(s)", self.schedules.len())?;
+        if let Some(now) = self.now {
+            write!(f, " at time {now}")?;
+        }
+        for schedule in &self.schedules {
+            write!(f, "\n\n{schedule}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct EscrowEntry {
+    index: u64,
+    escrow_id: String,
+    holding_id: String,
+    vault_holding_id: String,
+    beneficiary: String,
+    arbiter: String,
+    amount: u128,
+    deadline: u64,
+    status: String,
+    balance: Option<u128>,
+}
+
+impl From<&EscrowSnapshot> for EscrowEntry {
+    fn from(snapshot: &EscrowSnapshot) -> Self {
+        let escrow = &snapshot.escrow;
+        Self {
+            index: snapshot.index,
+            escrow_id: snapshot.escrow_id.to_string(),
+            holding_id: snapshot.holding_id.to_string(),
+            vault_holding_id: escrow.vault_id.to_string(),
+            beneficiary: escrow.beneficiary.to_string(),
+            arbiter: escrow.arbiter.to_string(),
+            amount: escrow.amount,
+            deadline: escrow.deadline,
+            status: format!("{:?}", escrow.status),
+            balance: snapshot.balance,
+        }
+    }
+}
+
+impl fmt::Display for EscrowEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Escrow {}:               {}", self.index, self.escrow_id)?;
+        writeln!(f, "Status:                 {}", self.status)?;
+        writeln!(f, "Vault holding PDA:      {}", self.vault_holding_id)?;
+        writeln!(f, "Beneficiary:            {}", self.beneficiary)?;
+        writeln!(f, "Arbiter:                {}", self.arbiter)?;
+        writeln!(f, "Amount:                 {}", self.amount)?;
+        writeln!(f, "Deadline:               {}", self.deadline)?;
+        match self.balance {
+            Some(balance) => write!(f, "Held:                   {balance}"),
+            None => write!(f, "Held:                   (uninitialized)"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct EscrowList {
+    escrows: Vec<EscrowEntry>,
+}
+
+impl fmt::Display for EscrowList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} escrow(s)", self.escrows.len())?;
+        for escrow in &self.escrows {
+            write!(f, "\n\n{escrow}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct LedgerRow {
+    seq: u64,
+    kind: String,
+    vault: String,
+    counterparty: String,
+    amount: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<String>,
+}
+
+impl From<&LedgerEntry> for LedgerRow {
+    fn from(entry: &LedgerEntry) -> Self {
+        Self {
+            seq: entry.seq,
+            kind: format!("{:?}", entry.kind),
+            vault: entry.vault.to_string(),
+            counterparty: entry.counterparty.to_string(),
+            amount: entry.amount,
+            memo: entry.memo.clone(),
+        }
+    }
+}
+
+impl fmt::Display for LedgerRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Entry {}:", self.seq)?;
+        writeln!(f, "Kind:                   {}", self.kind)?;
+        writeln!(f, "Vault holding:          {}", self.vault)?;
+        writeln!(f, "Counterparty:           {}", self.counterparty)?;
+        write!(f, "Amount:                 {}", self.amount)?;
+        if let Some(memo) = &self.memo {
+            write!(f, "\nMemo:                   {memo}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct LedgerList {
+    entries: Vec<LedgerRow>,
+}
+
+impl fmt::Display for LedgerList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ledger entries", self.entries.len())?;
+        for entry in &self.entries {
+            write!(f, "\n\n{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct InvoiceEntry {
+    index: u64,
+    invoice_id: String,
+    vault_holding_id: String,
+    amount: u128,
+    payer: Option<String>,
+    due: u64,
+    reference: String,
+    status: String,
+    paid_by: Option<String>,
+}
+
+impl From<&InvoiceSnapshot> for InvoiceEntry {
+    fn from(snapshot: &InvoiceSnapshot) -> Self {
+        let invoice = &snapshot.invoice;
+        Self {
+            index: snapshot.index,
+            invoice_id: snapshot.invoice_id.to_string(),
+            vault_holding_id: invoice.vault_id.to_string(),
+            amount: invoice.amount,
+            payer: invoice.payer.map(|payer| payer.to_string()),
+            due: invoice.due,
+            reference: invoice.reference.clone(),
+            status: format!("{:?}", invoice.status),
+            paid_by: invoice.paid_by.map(|payer| payer.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for InvoiceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Invoice {}:              {}", self.index, self.invoice_id)?;
+        writeln!(f, "Status:                 {}", self.status)?;
+        writeln!(f, "Reference:              {}", self.reference)?;
+        writeln!(f, "Vault holding PDA:      {}", self.vault_holding_id)?;
+        match &self.payer {
+            Some(payer) => writeln!(f, "Payer:                  {payer}")?,
+            None => writeln!(f, "Payer:                  (anyone)")?,
+        }
+        writeln!(f, "Amount:                 {}", self.amount)?;
+        write!(f, "Due:                    {}", self.due)?;
+        if let Some(paid_by) = &self.paid_by {
+            write!(f, "\nPaid by:                {paid_by}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct MetadataEntry {
+    metadata_id: String,
+    metadata: Option<TreasuryMetadata>,
+}
+
+impl fmt::Display for MetadataEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Metadata PDA:           {}", self.metadata_id)?;
+        let Some(metadata) = &self.metadata else {
+            return write!(f, "\n(no metadata set)");
+        };
+        write!(f, "\nName:                   {}", metadata.name)?;
+        if !metadata.uri.is_empty() {
+            write!(f, "\nURI:                    {}", metadata.uri)?;
+        }
+        if !metadata.tags.is_empty() {
+            write!(f, "\nTags:                   {}", metadata.tags.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct InvoiceList {
+    invoices: Vec<InvoiceEntry>,
+}
+
+impl fmt::Display for InvoiceList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} invoice(s)", self.invoices.len())?;
+        for invoice in &self.invoices {
+            write!(f, "\n\n{invoice}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct MilestoneEntry {
+    amount: u128,
+    state: String,
+}
+
+#[derive(Serialize)]
+struct GrantEntry {
+    index: u64,
+    grant_id: String,
+    holding_id: String,
+    vault_holding_id: String,
+    grantee: String,
+    milestones: Vec<MilestoneEntry>,
+    progress: GrantProgress,
+}
+
+impl From<&GrantSnapshot> for GrantEntry {
+    fn from(snapshot: &GrantSnapshot) -> Self {
+        let grant = &snapshot.grant;
+        Self {
+            index: snapshot.index,
+            grant_id: snapshot.grant_id.to_string(),
+            holding_id: snapshot.holding_id.to_string(),
+            vault_holding_id: grant.vault_id.to_string(),
+            grantee: grant.grantee.to_string(),
+            milestones: grant
+                .milestones
+                .iter()
+                .map(|milestone| MilestoneEntry {
+                    amount: milestone.amount,
+                    state: format!("{:?}", milestone.state),
+                })
+                .collect(),
+            progress: snapshot.progress,
+        }
+    }
+}
+
+impl fmt::Display for GrantEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let progress = &self.progress;
+        writeln!(f, "Grant {}:                {}", self.index, self.grant_id)?;
+        writeln!(f, "Grantee:                {}", self.grantee)?;
+        writeln!(f, "Vault holding PDA:      {}", self.vault_holding_id)?;
+        writeln!(
+            f,
+            "Released:               {} of {} milestone(s), {} paid",
+            progress.released_milestones, progress.milestones, progress.released
+        )?;
+        writeln!(f, "Pending:                {}", progress.pending)?;
+        write!(f, "Cancelled:              {}", progress.cancelled)?;
+        for (index, milestone) in self.milestones.iter().enumerate() {
+            write!(f, "\n  #{index:<3} {:>20}  {}", milestone.amount, milestone.state)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct GrantList {
+    grants: Vec<GrantEntry>,
+}
+
+impl fmt::Display for GrantList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} grant(s)", self.grants.len())?;
+        for grant in &self.grants {
+            write!(f, "\n\n{grant}")?;
+        }
+        Ok(())
+    }
+}
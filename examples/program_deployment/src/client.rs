@@ -0,0 +1,1205 @@
+//! Transaction builders for the Treasury program.
+//!
+//! Every builder computes the treasury PDAs from the configured program ID
+//! and treasury ID, so callers only supply the accounts they own (token definitions,
+//! recipients, senders). Transactions are dry-run locally before they are
+//! submitted, so a rejected instruction never reaches the sequencer.
+
+use anyhow::{Context, Result, anyhow, bail};
+use borsh::BorshDeserialize;
+use nssa::{
+    AccountId, PublicTransaction,
+    public_transaction::{Message, WitnessSet},
+};
+use nssa_core::account::Account;
+use nssa_core::program::ProgramId;
+use treasury_core::{
+    Instruction, LEDGER_PAGE_ENTRIES, Role, SplitConfig, TokenHolding, TokenMetadata, TreasuryId,
+    TreasuryMetadata, TreasuryState, clock_account_id, compute_escrow_holding_pda,
+    compute_escrow_pda, compute_grant_holding_pda, compute_grant_pda, compute_invoice_pda,
+    compute_labeled_vault_pda, compute_ledger_page_pda, compute_metadata_pda,
+    compute_schedule_pda, compute_share_definition_pda, compute_token_definition_pda,
+    compute_treasury_state_pda, compute_vault_holding_pda, compute_vault_pda,
+};
+use wallet::WalletCore;
+
+use crate::config::TreasuryConfig;
+use crate::dry_run::{self, DryRun};
+
+/// A treasury instruction together with the accounts it runs on.
+#[derive(Debug, Clone)]
+pub struct TreasuryTx {
+    /// Accounts in the order the instruction expects them, except the
+    /// trailing ledger page. Which page the entry lands on depends on the
+    /// ledger's length when the transaction runs, so [`TreasuryClient::dry_run`]
+    /// and [`TreasuryClient::submit`] append it.
+    pub account_ids: Vec<AccountId>,
+    pub instruction: Instruction,
+    /// Accounts that must sign with their wallet key.
+    pub signers: Vec<AccountId>,
+}
+
+/// Terms of a new payment schedule. See `Instruction::CreateSchedule`.
+#[derive(Debug, Clone, Copy)]
+pub struct ScheduleTerms {
+    pub recipient: AccountId,
+    pub amount: u128,
+    pub interval: u64,
+    pub start: u64,
+    pub count: u64,
+}
+
+/// Terms of a new escrow. See `Instruction::CreateEscrow`.
+#[derive(Debug, Clone, Copy)]
+pub struct EscrowTerms {
+    pub beneficiary: AccountId,
+    pub amount: u128,
+    pub arbiter: AccountId,
+    pub deadline: u64,
+}
+
+/// Terms of a new invoice. See `Instruction::CreateInvoice`.
+#[derive(Debug, Clone)]
+pub struct InvoiceTerms {
+    pub amount: u128,
+    pub payer: Option<AccountId>,
+    pub due: u64,
+    pub reference: String,
+}
+
+/// An AMM pool and its two token vaults, in pool order.
+#[derive(Debug, Clone, Copy)]
+pub struct AmmPool {
+    pub pool: AccountId,
+    pub vault_a: AccountId,
+    pub vault_b: AccountId,
+}
+
+/// Client for one deployed treasury.
+pub struct TreasuryClient {
+    wallet: WalletCore,
+    config: TreasuryConfig,
+}
+
+impl TreasuryClient {
+    pub fn new(wallet: WalletCore, config: TreasuryConfig) -> Self {
+        Self { wallet, config }
+    }
+
+    pub fn config(&self) -> &TreasuryConfig {
+        &self.config
+    }
+
+    pub(crate) fn wallet(&self) -> &WalletCore {
+        &self.wallet
+    }
+
+    fn amm_program_id(&self) -> Result<ProgramId> {
+        self.config
+            .amm_program_id
+            .context("no AMM program configured (run `treasury init --amm-bin ...`)")
+    }
+
+    /// The treasury state PDA.
+    pub fn treasury_state_id(&self) -> AccountId {
+        compute_treasury_state_pda(&self.config.treasury_program_id, &self.config.treasury_id())
+    }
+
+    /// The vault holding PDA for a token definition.
+    pub fn vault_holding_id(&self, token_definition_id: &AccountId) -> AccountId {
+        compute_vault_holding_pda(
+            &self.config.treasury_program_id,
+            &self.config.treasury_id(),
+            token_definition_id,
+        )
+    }
+
+    /// The holding PDA of a token's vault labeled `label`.
+    pub fn labeled_vault_id(&self, token_definition_id: &AccountId, label: &str) -> AccountId {
+        compute_labeled_vault_pda(
+            &self.config.treasury_program_id,
+            &self.config.treasury_id(),
+            token_definition_id,
+            label,
+        )
+    }
+
+    /// The holding PDA of a token's main vault (`None`) or labeled vault.
+    pub fn vault_id(&self, token_definition_id: &AccountId, label: Option<&str>) -> AccountId {
+        compute_vault_pda(
+            &self.config.treasury_program_id,
+            &self.config.treasury_id(),
+            token_definition_id,
+            label,
+        )
+    }
+
+    /// The share definition PDA for a vault's token definition.
+    pub fn share_definition_id(&self, token_definition_id: &AccountId) -> AccountId {
+        compute_share_definition_pda(
+            &self.config.treasury_program_id,
+            &self.config.treasury_id(),
+            token_definition_id,
+        )
+    }
+
+    /// The treasury-minted token definition PDA for the vault at registry
+    /// position `index`.
+    pub fn token_definition_id(&self, index: u64) -> AccountId {
+        compute_token_definition_pda(
+            &self.config.treasury_program_id,
+            &self.config.treasury_id(),
+            index,
+        )
+    }
+
+    /// The token definition PDA the next vault can be created with, so the
+    /// treasury can later mint more of it. Reads the treasury state.
+    pub async fn next_token_definition_id(&self) -> Result<AccountId> {
+        let state = self.fetch_state().await?;
+        Ok(self.token_definition_id(state.vaults.len() as u64))
+    }
+
+    /// The PDA of a vault's `index`-th payment schedule.
+    pub fn schedule_id(&self, token_definition_id: &AccountId, index: u64) -> AccountId {
+        compute_schedule_pda(
+            &self.config.treasury_program_id,
+            &self.config.treasury_id(),
+            token_definition_id,
+            index,
+        )
+    }
+
+    /// The PDA holding an escrow's terms.
+    pub fn escrow_id(&self, escrow: u64) -> AccountId {
+        compute_escrow_pda(&self.config.treasury_program_id, &self.config.treasury_id(), escrow)
+    }
+
+    /// The token holding PDA holding an escrow's funds.
+    pub fn escrow_holding_id(&self, escrow: u64) -> AccountId {
+        compute_escrow_holding_pda(
+            &self.config.treasury_program_id,
+            &self.config.treasury_id(),
+            escrow,
+        )
+    }
+
+    /// The PDA holding a grant's milestones.
+    pub fn grant_id(&self, grant: u64) -> AccountId {
+        compute_grant_pda(&self.config.treasury_program_id, &self.config.treasury_id(), grant)
+    }
+
+    /// The token holding PDA holding a grant's unreleased funds.
+    pub fn grant_holding_id(&self, grant: u64) -> AccountId {
+        compute_grant_holding_pda(
+            &self.config.treasury_program_id,
+            &self.config.treasury_id(),
+            grant,
+        )
+    }
+
+    /// The PDA holding an invoice.
+    pub fn invoice_id(&self, invoice: u64) -> AccountId {
+        compute_invoice_pda(&self.config.treasury_program_id, &self.config.treasury_id(), invoice)
+    }
+
+    /// The PDA holding the treasury's metadata.
+    pub fn metadata_id(&self) -> AccountId {
+        compute_metadata_pda(&self.config.treasury_program_id, &self.config.treasury_id())
+    }
+
+    /// The PDA holding the `page`-th page of ledger entries.
+    pub fn ledger_page_id(&self, page: u64) -> AccountId {
+        compute_ledger_page_pda(&self.config.treasury_program_id, &self.config.treasury_id(), page)
+    }
+
+    /// The ledger page the next entry is appended to. Reads the treasury
+    /// state.
+    pub async fn current_ledger_page_id(&self) -> Result<AccountId> {
+        let ledger_len = self.fetch_state().await?.ledger_len;
+        Ok(self.ledger_page_id(ledger_len / LEDGER_PAGE_ENTRIES))
+    }
+
+    /// Create the configured treasury with its creator as admin. The creator
+    /// signs.
+    ///
+    /// Accounts: `[treasury_state, admin]`.
+    pub fn initialize_tx(&self) -> TreasuryTx {
+        let creator_id = self.config.treasury_creator;
+        TreasuryTx {
+            account_ids: vec![self.treasury_state_id(), creator_id],
+            instruction: Instruction::Initialize {
+                name: self.config.treasury_name.clone(),
+                treasury_program_id: self.config.treasury_program_id,
+            },
+            signers: vec![creator_id],
+        }
+    }
+
+    /// Create a recurring payment from a vault. Reads the treasury state to
+    /// find the vault's next schedule index. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, vault_holding, schedule, admin]`.
+    pub async fn create_schedule_tx(
+        &self,
+        token_definition_id: AccountId,
+        admin_id: AccountId,
+        terms: &ScheduleTerms,
+    ) -> Result<TreasuryTx> {
+        let state = self.fetch_state().await?;
+        let Some(vault) = state.vault(&token_definition_id) else {
+            bail!("no vault registered for {token_definition_id}");
+        };
+        Ok(TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                vault.holding_id,
+                self.schedule_id(&token_definition_id, vault.schedule_count),
+                admin_id,
+            ],
+            instruction: Instruction::CreateSchedule {
+                recipient: terms.recipient,
+                amount: terms.amount,
+                interval: terms.interval,
+                start: terms.start,
+                count: terms.count,
+            },
+            signers: vec![admin_id],
+        })
+    }
+
+    /// Pay the installments of a schedule that have fallen due. Reads the
+    /// schedule for the recipient.
+    ///
+    /// Accounts: `[treasury_state, vault_holding, schedule, recipient_holding,
+    /// clock]`.
+    pub async fn execute_due_tx(
+        &self,
+        token_definition_id: AccountId,
+        index: u64,
+    ) -> Result<TreasuryTx> {
+        let schedule = self.schedule(token_definition_id, index).await?;
+        Ok(TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.vault_holding_id(&token_definition_id),
+                schedule.schedule_id,
+                schedule.schedule.recipient,
+                clock_account_id(),
+            ],
+            instruction: Instruction::ExecuteDue {
+                schedule: index,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![],
+        })
+    }
+
+    /// Mint more of a treasury-created token into its vault. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, token_definition, vault_holding, admin]`.
+    pub fn mint_more_tx(
+        &self,
+        token_definition_id: AccountId,
+        admin_id: AccountId,
+        amount: u128,
+    ) -> TreasuryTx {
+        TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                token_definition_id,
+                self.vault_holding_id(&token_definition_id),
+                admin_id,
+            ],
+            instruction: Instruction::MintMore {
+                amount,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![admin_id],
+        }
+    }
+
+    /// Burn tokens held by a vault. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, token_definition, vault_holding, admin]`.
+    pub fn burn_tx(
+        &self,
+        token_definition_id: AccountId,
+        admin_id: AccountId,
+        amount: u128,
+    ) -> TreasuryTx {
+        TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                token_definition_id,
+                self.vault_holding_id(&token_definition_id),
+                admin_id,
+            ],
+            instruction: Instruction::Burn {
+                amount,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![admin_id],
+        }
+    }
+
+    /// Set or clear a vault's supply cap. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, vault_holding, admin]`.
+    pub fn set_supply_cap_tx(
+        &self,
+        token_definition_id: AccountId,
+        admin_id: AccountId,
+        cap: Option<u128>,
+    ) -> TreasuryTx {
+        TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.vault_holding_id(&token_definition_id),
+                admin_id,
+            ],
+            instruction: Instruction::SetSupplyCap { cap },
+            signers: vec![admin_id],
+        }
+    }
+
+    /// Set or clear how a token's split deposits are divided. The admin
+    /// signs.
+    ///
+    /// Accounts: `[treasury_state, vault_holding, admin]`.
+    pub fn set_split_tx(
+        &self,
+        token_definition_id: AccountId,
+        admin_id: AccountId,
+        split: Option<SplitConfig>,
+    ) -> TreasuryTx {
+        TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.vault_holding_id(&token_definition_id),
+                admin_id,
+            ],
+            instruction: Instruction::SetSplit { split },
+            signers: vec![admin_id],
+        }
+    }
+
+    /// Freeze a vault, or unfreeze it when `frozen` is false. The signer must
+    /// be the admin or a guardian.
+    ///
+    /// Accounts: `[treasury_state, vault_holding, signer]`.
+    pub fn set_vault_frozen_tx(
+        &self,
+        token_definition_id: AccountId,
+        signer_id: AccountId,
+        frozen: bool,
+    ) -> TreasuryTx {
+        TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.vault_holding_id(&token_definition_id),
+                signer_id,
+            ],
+            instruction: if frozen {
+                Instruction::FreezeVault
+            } else {
+                Instruction::UnfreezeVault
+            },
+            signers: vec![signer_id],
+        }
+    }
+
+    /// Set the treasury's name, description URI and tags, replacing any
+    /// earlier metadata. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, metadata, admin]`.
+    pub fn set_metadata_tx(&self, admin_id: AccountId, metadata: &TreasuryMetadata) -> TreasuryTx {
+        TreasuryTx {
+            account_ids: vec![self.treasury_state_id(), self.metadata_id(), admin_id],
+            instruction: Instruction::SetMetadata {
+                name: metadata.name.clone(),
+                uri: metadata.uri.clone(),
+                tags: metadata.tags.clone(),
+            },
+            signers: vec![admin_id],
+        }
+    }
+
+    /// Deposit tokens from one of the wallet's holdings into the labeled
+    /// vaults of the token's split. Reads the treasury state for the split.
+    /// The sender signs.
+    ///
+    /// Accounts: `[treasury_state, sender_holding, target_vault...]`, one
+    /// labeled vault per split target.
+    pub async fn deposit_split_tx(
+        &self,
+        token_definition_id: AccountId,
+        sender_id: AccountId,
+        amount: u128,
+    ) -> Result<TreasuryTx> {
+        let state = self.fetch_state().await?;
+        let Some(split) = state
+            .vault(&token_definition_id)
+            .and_then(|vault| vault.split.as_ref())
+        else {
+            bail!("no split is set for token {token_definition_id}");
+        };
+        let mut account_ids = vec![self.treasury_state_id(), sender_id];
+        account_ids.extend(
+            split
+                .targets
+                .iter()
+                .map(|target| self.labeled_vault_id(&token_definition_id, &target.label)),
+        );
+        Ok(TreasuryTx {
+            account_ids,
+            instruction: Instruction::DepositSplit {
+                amount,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![sender_id],
+        })
+    }
+
+    /// Move tokens between two vaults of a token, each the main vault
+    /// (`None`) or a labeled vault. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, from_vault, to_vault, admin]`.
+    pub fn internal_transfer_tx(
+        &self,
+        token_definition_id: AccountId,
+        from_label: Option<String>,
+        to_label: Option<String>,
+        admin_id: AccountId,
+        amount: u128,
+    ) -> TreasuryTx {
+        TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.vault_id(&token_definition_id, from_label.as_deref()),
+                self.vault_id(&token_definition_id, to_label.as_deref()),
+                admin_id,
+            ],
+            instruction: Instruction::InternalTransfer {
+                from_vault: from_label,
+                to_vault: to_label,
+                amount,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![admin_id],
+        }
+    }
+
+    /// Pay from a vault into the same token's vault of another treasury, in
+    /// this deployment or another, through that treasury's Deposit. The
+    /// shares it mints go to this treasury's vault PDA of its share
+    /// definition. Reads the target's state for its ledger page. The admin
+    /// signs.
+    ///
+    /// Accounts: `[treasury_state, vault_holding, target_state, target_vault,
+    /// target_share_definition, share_holding, admin, target_ledger_page]`.
+    pub async fn fund_treasury_tx(
+        &self,
+        target_treasury_program_id: ProgramId,
+        target_treasury: TreasuryId,
+        token_definition_id: AccountId,
+        admin_id: AccountId,
+        amount: u128,
+    ) -> Result<TreasuryTx> {
+        Ok(TreasuryTx {
+            account_ids: self
+                .target_treasury_accounts(
+                    &target_treasury_program_id,
+                    &target_treasury,
+                    &token_definition_id,
+                    admin_id,
+                )
+                .await?,
+            instruction: Instruction::FundTreasury {
+                target_treasury_program_id,
+                target_treasury,
+                token_definition: token_definition_id,
+                amount,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![admin_id],
+        })
+    }
+
+    /// Redeem shares that [`TreasuryClient::fund_treasury_tx`] received from
+    /// another treasury, through that treasury's Redeem. The payout goes back
+    /// into this treasury's vault of the token. Reads the target's state for
+    /// its ledger page. The admin signs.
+    ///
+    /// Accounts as for [`TreasuryClient::fund_treasury_tx`].
+    pub async fn redeem_from_treasury_tx(
+        &self,
+        target_treasury_program_id: ProgramId,
+        target_treasury: TreasuryId,
+        token_definition_id: AccountId,
+        admin_id: AccountId,
+        shares: u128,
+    ) -> Result<TreasuryTx> {
+        Ok(TreasuryTx {
+            account_ids: self
+                .target_treasury_accounts(
+                    &target_treasury_program_id,
+                    &target_treasury,
+                    &token_definition_id,
+                    admin_id,
+                )
+                .await?,
+            instruction: Instruction::RedeemFromTreasury {
+                target_treasury_program_id,
+                target_treasury,
+                token_definition: token_definition_id,
+                shares,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![admin_id],
+        })
+    }
+
+    /// Accounts of FundTreasury and RedeemFromTreasury, less the ledger page.
+    async fn target_treasury_accounts(
+        &self,
+        target: &ProgramId,
+        target_id: &TreasuryId,
+        token_definition_id: &AccountId,
+        admin_id: AccountId,
+    ) -> Result<Vec<AccountId>> {
+        let target_share_definition_id =
+            compute_share_definition_pda(target, target_id, token_definition_id);
+        let target_ledger_len = self.fetch_state_of(target, target_id).await?.ledger_len;
+        Ok(vec![
+            self.treasury_state_id(),
+            self.vault_holding_id(token_definition_id),
+            compute_treasury_state_pda(target, target_id),
+            compute_vault_holding_pda(target, target_id, token_definition_id),
+            target_share_definition_id,
+            self.vault_holding_id(&target_share_definition_id),
+            admin_id,
+            compute_ledger_page_pda(target, target_id, target_ledger_len / LEDGER_PAGE_ENTRIES),
+        ])
+    }
+
+    /// Pin the configured AMM program as the one swaps and liquidity chain
+    /// to. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, admin]`.
+    pub fn set_amm_program_tx(&self, admin_id: AccountId) -> Result<TreasuryTx> {
+        Ok(TreasuryTx {
+            account_ids: vec![self.treasury_state_id(), admin_id],
+            instruction: Instruction::SetAmmProgram {
+                amm_program_id: self.amm_program_id()?,
+            },
+            signers: vec![admin_id],
+        })
+    }
+
+    /// Swap `amount_in` from one vault into another through an AMM pool. The
+    /// admin signs.
+    ///
+    /// Accounts: `[treasury_state, from_vault, to_vault, pool, pool_vault_a,
+    /// pool_vault_b, admin]`.
+    pub fn swap_tx(
+        &self,
+        from_token_definition_id: AccountId,
+        to_token_definition_id: AccountId,
+        pool: &AmmPool,
+        admin_id: AccountId,
+        amount_in: u128,
+        min_amount_out: u128,
+    ) -> TreasuryTx {
+        TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.vault_holding_id(&from_token_definition_id),
+                self.vault_holding_id(&to_token_definition_id),
+                pool.pool,
+                pool.vault_a,
+                pool.vault_b,
+                admin_id,
+            ],
+            instruction: Instruction::Swap {
+                amount_in,
+                min_amount_out,
+            },
+            signers: vec![admin_id],
+        }
+    }
+
+    /// Provide liquidity to an AMM pool from the vaults of its two tokens,
+    /// receiving LP tokens into the LP vault. Reads the pool vaults to find
+    /// the pool's tokens. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, vault_a, vault_b, lp_vault, pool,
+    /// pool_vault_a, pool_vault_b, pool_lp_definition, admin]`.
+    pub async fn add_liquidity_tx(
+        &self,
+        pool: &AmmPool,
+        lp_definition_id: AccountId,
+        admin_id: AccountId,
+        max_amount_a: u128,
+        max_amount_b: u128,
+        min_amount_lp: u128,
+    ) -> Result<TreasuryTx> {
+        Ok(TreasuryTx {
+            account_ids: self.liquidity_accounts(pool, lp_definition_id, admin_id).await?,
+            instruction: Instruction::AddLiquidity {
+                max_amount_a,
+                max_amount_b,
+                min_amount_lp,
+            },
+            signers: vec![admin_id],
+        })
+    }
+
+    /// Withdraw liquidity from an AMM pool into the vaults of its two tokens,
+    /// paying `lp_amount` from the LP vault. The admin signs.
+    ///
+    /// Accounts as for [`Self::add_liquidity_tx`].
+    pub async fn remove_liquidity_tx(
+        &self,
+        pool: &AmmPool,
+        lp_definition_id: AccountId,
+        admin_id: AccountId,
+        lp_amount: u128,
+        min_amount_a: u128,
+        min_amount_b: u128,
+    ) -> Result<TreasuryTx> {
+        Ok(TreasuryTx {
+            account_ids: self.liquidity_accounts(pool, lp_definition_id, admin_id).await?,
+            instruction: Instruction::RemoveLiquidity {
+                lp_amount,
+                min_amount_a,
+                min_amount_b,
+            },
+            signers: vec![admin_id],
+        })
+    }
+
+    async fn liquidity_accounts(
+        &self,
+        pool: &AmmPool,
+        lp_definition_id: AccountId,
+        admin_id: AccountId,
+    ) -> Result<Vec<AccountId>> {
+        let mut vault_ids = Vec::with_capacity(2);
+        for pool_vault in [pool.vault_a, pool.vault_b] {
+            let account = self.fetch_account(pool_vault).await?;
+            let Some(holding) = TokenHolding::decode(&account.data) else {
+                bail!("pool vault {pool_vault} is not a token holding");
+            };
+            vault_ids.push(self.vault_holding_id(&holding.definition_id));
+        }
+        Ok(vec![
+            self.treasury_state_id(),
+            vault_ids[0],
+            vault_ids[1],
+            self.vault_holding_id(&lp_definition_id),
+            pool.pool,
+            pool.vault_a,
+            pool.vault_b,
+            lp_definition_id,
+            admin_id,
+        ])
+    }
+
+    /// Move funds from a vault into a new escrow. Reads the treasury state to
+    /// find the next escrow ID. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, vault_holding, escrow, escrow_holding,
+    /// admin]`.
+    pub async fn create_escrow_tx(
+        &self,
+        token_definition_id: AccountId,
+        admin_id: AccountId,
+        terms: &EscrowTerms,
+    ) -> Result<TreasuryTx> {
+        let escrow = self.fetch_state().await?.escrow_count;
+        Ok(TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.vault_holding_id(&token_definition_id),
+                self.escrow_id(escrow),
+                self.escrow_holding_id(escrow),
+                admin_id,
+            ],
+            instruction: Instruction::CreateEscrow {
+                payer_vault: self.vault_holding_id(&token_definition_id),
+                beneficiary: terms.beneficiary,
+                amount: terms.amount,
+                arbiter: terms.arbiter,
+                deadline: terms.deadline,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![admin_id],
+        })
+    }
+
+    /// Request a payment into a vault. Reads the treasury state to find the
+    /// next invoice ID. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, vault_holding, invoice, admin]`.
+    pub async fn create_invoice_tx(
+        &self,
+        token_definition_id: AccountId,
+        admin_id: AccountId,
+        terms: &InvoiceTerms,
+    ) -> Result<TreasuryTx> {
+        let invoice = self.fetch_state().await?.invoice_count;
+        Ok(TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.vault_holding_id(&token_definition_id),
+                self.invoice_id(invoice),
+                admin_id,
+            ],
+            instruction: Instruction::CreateInvoice {
+                amount: terms.amount,
+                payer: terms.payer,
+                due: terms.due,
+                reference: terms.reference.clone(),
+            },
+            signers: vec![admin_id],
+        })
+    }
+
+    /// Pay an invoice from one of the wallet's holdings. The program rejects
+    /// any `amount` other than the amount due, and payments after the due
+    /// time. The payer signs.
+    ///
+    /// Accounts: `[treasury_state, invoice, payer_holding, vault_holding,
+    /// clock]`.
+    pub async fn pay_invoice_tx(
+        &self,
+        invoice: u64,
+        payer_id: AccountId,
+        amount: u128,
+    ) -> Result<TreasuryTx> {
+        let record = self.invoice(invoice).await?.invoice;
+        Ok(TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.invoice_id(invoice),
+                payer_id,
+                record.vault_id,
+                clock_account_id(),
+            ],
+            instruction: Instruction::PayInvoice {
+                invoice,
+                amount,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![payer_id],
+        })
+    }
+
+    /// Pay an escrow to its beneficiary. The arbiter signs.
+    ///
+    /// Accounts: `[treasury_state, escrow, escrow_holding, beneficiary_holding,
+    /// arbiter]`.
+    pub async fn release_escrow_tx(&self, escrow: u64) -> Result<TreasuryTx> {
+        let record = self.escrow(escrow).await?.escrow;
+        Ok(TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.escrow_id(escrow),
+                self.escrow_holding_id(escrow),
+                record.beneficiary,
+                record.arbiter,
+            ],
+            instruction: Instruction::Release {
+                escrow,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![record.arbiter],
+        })
+    }
+
+    /// Return an escrow to its vault. The arbiter signs.
+    ///
+    /// Accounts: `[treasury_state, escrow, escrow_holding, vault_holding,
+    /// arbiter]`.
+    pub async fn refund_escrow_tx(&self, escrow: u64) -> Result<TreasuryTx> {
+        let record = self.escrow(escrow).await?.escrow;
+        Ok(TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.escrow_id(escrow),
+                self.escrow_holding_id(escrow),
+                record.vault_id,
+                record.arbiter,
+            ],
+            instruction: Instruction::Refund {
+                escrow,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![record.arbiter],
+        })
+    }
+
+    /// Return an escrow to its vault after its deadline. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, escrow, escrow_holding, vault_holding,
+    /// admin, clock]`.
+    pub async fn reclaim_escrow_tx(&self, escrow: u64, admin_id: AccountId) -> Result<TreasuryTx> {
+        let record = self.escrow(escrow).await?.escrow;
+        Ok(TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.escrow_id(escrow),
+                self.escrow_holding_id(escrow),
+                record.vault_id,
+                admin_id,
+                clock_account_id(),
+            ],
+            instruction: Instruction::Reclaim {
+                escrow,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![admin_id],
+        })
+    }
+
+    /// Give `account` a role. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, admin]`.
+    pub fn assign_role_tx(
+        &self,
+        admin_id: AccountId,
+        role: Role,
+        account: AccountId,
+    ) -> TreasuryTx {
+        TreasuryTx {
+            account_ids: vec![self.treasury_state_id(), admin_id],
+            instruction: Instruction::AssignRole { role, account },
+            signers: vec![admin_id],
+        }
+    }
+
+    /// Take a role away from `account`. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, admin]`.
+    pub fn revoke_role_tx(
+        &self,
+        admin_id: AccountId,
+        role: Role,
+        account: AccountId,
+    ) -> TreasuryTx {
+        TreasuryTx {
+            account_ids: vec![self.treasury_state_id(), admin_id],
+            instruction: Instruction::RevokeRole { role, account },
+            signers: vec![admin_id],
+        }
+    }
+
+    /// Fund a milestone grant from a vault. Reads the treasury state to find
+    /// the next grant ID. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, vault_holding, grant, grant_holding,
+    /// admin]`.
+    pub async fn create_grant_tx(
+        &self,
+        token_definition_id: AccountId,
+        admin_id: AccountId,
+        grantee: AccountId,
+        milestones: Vec<u128>,
+    ) -> Result<TreasuryTx> {
+        let grant = self.fetch_state().await?.grant_count;
+        Ok(TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.vault_holding_id(&token_definition_id),
+                self.grant_id(grant),
+                self.grant_holding_id(grant),
+                admin_id,
+            ],
+            instruction: Instruction::CreateGrant {
+                grantee,
+                milestones,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![admin_id],
+        })
+    }
+
+    /// Release a grant milestone to the grantee. The reviewer signs.
+    ///
+    /// Accounts: `[treasury_state, grant, grant_holding, grantee_holding,
+    /// reviewer]`.
+    pub async fn approve_milestone_tx(
+        &self,
+        grant: u64,
+        milestone: u32,
+        reviewer_id: AccountId,
+    ) -> Result<TreasuryTx> {
+        let record = self.grant(grant).await?.grant;
+        Ok(TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.grant_id(grant),
+                self.grant_holding_id(grant),
+                record.grantee,
+                reviewer_id,
+            ],
+            instruction: Instruction::ApproveMilestone {
+                grant,
+                milestone,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![reviewer_id],
+        })
+    }
+
+    /// Cancel a grant's pending milestones and return their funds to the
+    /// vault. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, grant, grant_holding, vault_holding,
+    /// admin]`.
+    pub async fn cancel_grant_tx(&self, grant: u64, admin_id: AccountId) -> Result<TreasuryTx> {
+        let record = self.grant(grant).await?.grant;
+        Ok(TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.grant_id(grant),
+                self.grant_holding_id(grant),
+                record.vault_id,
+                admin_id,
+            ],
+            instruction: Instruction::CancelGrant {
+                grant,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![admin_id],
+        })
+    }
+
+    /// Create a new token and mint its initial supply into the treasury vault,
+    /// recording how to render it. The program rejects token names longer
+    /// than [`MAX_TOKEN_NAME_LEN`](treasury_core::MAX_TOKEN_NAME_LEN) bytes.
+    /// The admin signs, so the treasury must have been initialized.
+    ///
+    /// Accounts: `[treasury_state, token_definition, vault_holding, admin]`.
+    pub fn create_vault_tx(
+        &self,
+        token_definition_id: AccountId,
+        admin_id: AccountId,
+        token_name: &str,
+        metadata: &TokenMetadata,
+        initial_supply: u128,
+    ) -> TreasuryTx {
+        TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                token_definition_id,
+                self.vault_holding_id(&token_definition_id),
+                admin_id,
+            ],
+            instruction: Instruction::CreateVault {
+                token_name: token_name.to_string(),
+                metadata: metadata.clone(),
+                initial_supply,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![admin_id],
+        }
+    }
+
+    /// Send tokens from the vault to a recipient holding, with an optional
+    /// memo recorded in the ledger. The admin signs.
+    ///
+    /// Accounts: `[treasury_state, vault_holding, recipient_holding, admin]`.
+    pub fn send_tx(
+        &self,
+        token_definition_id: AccountId,
+        recipient_id: AccountId,
+        admin_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) -> TreasuryTx {
+        TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.vault_holding_id(&token_definition_id),
+                recipient_id,
+                admin_id,
+            ],
+            instruction: Instruction::Send {
+                amount,
+                token_program_id: self.config.token_program_id,
+                memo,
+                private_recipient: false,
+            },
+            signers: vec![admin_id],
+        }
+    }
+
+    /// Deposit tokens from one of the wallet's holdings into the vault, and
+    /// receive vault shares into `share_holding_id`. The optional memo is
+    /// recorded in the ledger.
+    ///
+    /// Accounts: `[treasury_state, sender_holding, vault_holding,
+    /// share_definition, share_holding]`. The sender and share holding sign
+    /// with their wallet keys.
+    pub fn deposit_tx(
+        &self,
+        token_definition_id: AccountId,
+        sender_id: AccountId,
+        share_holding_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) -> TreasuryTx {
+        TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                sender_id,
+                self.vault_holding_id(&token_definition_id),
+                self.share_definition_id(&token_definition_id),
+                share_holding_id,
+            ],
+            instruction: Instruction::Deposit {
+                amount,
+                token_program_id: self.config.token_program_id,
+                memo,
+                private_sender: false,
+            },
+            signers: vec![sender_id, share_holding_id],
+        }
+    }
+
+    /// Burn vault shares and receive the proportional amount of the vault's
+    /// token into `recipient_id`.
+    ///
+    /// Accounts: `[treasury_state, vault_holding, share_definition,
+    /// share_holding, recipient_holding]`. The share holding signs with its
+    /// wallet key.
+    pub fn redeem_tx(
+        &self,
+        token_definition_id: AccountId,
+        share_holding_id: AccountId,
+        recipient_id: AccountId,
+        shares: u128,
+    ) -> TreasuryTx {
+        TreasuryTx {
+            account_ids: vec![
+                self.treasury_state_id(),
+                self.vault_holding_id(&token_definition_id),
+                self.share_definition_id(&token_definition_id),
+                share_holding_id,
+                recipient_id,
+            ],
+            instruction: Instruction::Redeem {
+                shares,
+                token_program_id: self.config.token_program_id,
+            },
+            signers: vec![share_holding_id],
+        }
+    }
+
+    /// Execute a transaction locally against the current account states,
+    /// without submitting it.
+    pub async fn dry_run(&self, tx: &TreasuryTx) -> Result<DryRun> {
+        let tx = self.with_ledger_page(tx).await?;
+        self.simulate(&tx).await
+    }
+
+    /// Dry-run a transaction and submit it if the program would accept it.
+    /// Returns the simulated account changes.
+    pub async fn submit(&self, tx: &TreasuryTx) -> Result<DryRun> {
+        let tx = self.with_ledger_page(tx).await?;
+        let simulated = self.simulate(&tx).await?;
+        self.submit_unchecked(&tx).await?;
+        Ok(simulated)
+    }
+
+    /// `tx` with the current ledger page appended to its accounts.
+    async fn with_ledger_page(&self, tx: &TreasuryTx) -> Result<TreasuryTx> {
+        let mut tx = tx.clone();
+        tx.account_ids.push(self.current_ledger_page_id().await?);
+        Ok(tx)
+    }
+
+    async fn simulate(&self, tx: &TreasuryTx) -> Result<DryRun> {
+        let mut pre_accounts = Vec::with_capacity(tx.account_ids.len());
+        for account_id in &tx.account_ids {
+            pre_accounts.push(self.fetch_account(*account_id).await?);
+        }
+        dry_run::simulate(&self.config, tx, pre_accounts)
+    }
+
+    /// Fetch an account from the sequencer.
+    pub async fn fetch_account(&self, account_id: AccountId) -> Result<Account> {
+        let response = self
+            .wallet
+            .sequencer_client
+            .get_account(account_id)
+            .await
+            .map_err(|err| anyhow!("failed to fetch account {account_id}: {err:?}"))?;
+        Ok(response.account)
+    }
+
+    /// Fetch and decode the treasury state. An uninitialized state PDA
+    /// decodes as the default (empty) state.
+    pub async fn fetch_state(&self) -> Result<TreasuryState> {
+        self.fetch_state_of(&self.config.treasury_program_id, &self.config.treasury_id())
+            .await
+    }
+
+    /// Fetch and decode the state of treasury `treasury` of the deployment
+    /// `treasury_program_id`, which may be another treasury than this
+    /// client's.
+    pub async fn fetch_state_of(
+        &self,
+        treasury_program_id: &ProgramId,
+        treasury: &TreasuryId,
+    ) -> Result<TreasuryState> {
+        let account = self
+            .fetch_account(compute_treasury_state_pda(treasury_program_id, treasury))
+            .await?;
+        if account == Account::default() {
+            return Ok(TreasuryState::default());
+        }
+        TreasuryState::try_from_slice(&account.data).context("failed to decode treasury state")
+    }
+
+    async fn submit_unchecked(&self, tx: &TreasuryTx) -> Result<()> {
+        let signers = &tx.signers;
+        let nonces = if signers.is_empty() {
+            vec![]
+        } else {
+            self.wallet
+                .get_accounts_nonces(signers.to_vec())
+                .await
+                .map_err(|err| anyhow!("failed to fetch signer nonces: {err:?}"))?
+        };
+        let signing_keys = signers
+            .iter()
+            .map(|signer| {
+                self.wallet
+                    .storage
+                    .user_data
+                    .get_pub_account_signing_key(signer)
+                    .ok_or_else(|| anyhow!("no signing key for {signer} in the wallet"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let message = Message::try_new(
+            self.config.treasury_program_id,
+            tx.account_ids.clone(),
+            nonces,
+            &tx.instruction,
+        )
+        .map_err(|err| anyhow!("failed to build transaction: {err:?}"))?;
+        let witness_set = WitnessSet::for_message(&message, &signing_keys);
+        let tx = PublicTransaction::new(message, witness_set);
+
+        self.wallet
+            .sequencer_client
+            .send_tx_public(tx)
+            .await
+            .map_err(|err| anyhow!("sequencer rejected transaction: {err:?}"))?;
+        Ok(())
+    }
+}
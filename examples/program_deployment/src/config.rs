@@ -0,0 +1,165 @@
+//! Program ID configuration for the treasury CLI.
+//!
+//! `treasury init` derives the program IDs from the compiled program binaries
+//! once and writes them to a TOML file, so later commands don't need the
+//! binaries at hand:
+//!
+//! ```toml
+//! treasury_program_id = [1, 2, 3, 4, 5, 6, 7, 8]
+//! token_program_id = [8, 7, 6, 5, 4, 3, 2, 1]
+//! # Which treasury of the deployment to act on: the one its creator
+//! # initialized under this name, which defaults to "main"
+//! treasury_creator = "6iArKUXxhUJqS7kCaPNhwMWt3ro71PDyBj7jwAyE2VQV"
+//! treasury_name = "main"
+//! # Optional, needed for swaps and liquidity
+//! amm_program_id = [2, 4, 6, 8, 1, 3, 5, 7]
+//! # Needed to prove privacy-preserving transactions
+//! treasury_bin = "programs/treasury.bin"
+//! token_bin = "programs/token.bin"
+//! ```
+//!
+//! One deployment can host many treasuries, each with its own PDAs derived
+//! from the treasury ID; the config names the one the CLI acts on. Names are
+//! scoped to the creator, who becomes the treasury's admin, so nobody else can
+//! take a name first.
+//!
+//! Private transactions execute the programs inside the privacy circuit, so
+//! for those the binaries must still be at the recorded paths.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail, ensure};
+use nssa::privacy_preserving_transaction::circuit::ProgramWithDependencies;
+use nssa::program::Program;
+use nssa_core::account::AccountId;
+use nssa_core::program::ProgramId;
+use serde::{Deserialize, Serialize};
+use treasury_core::TreasuryId;
+
+/// Default location of the config file, relative to the working directory.
+pub const DEFAULT_CONFIG_PATH: &str = "treasury.toml";
+
+/// Treasury name used when the config does not set one.
+pub const DEFAULT_TREASURY_NAME: &str = "main";
+
+/// Program IDs the CLI talks to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreasuryConfig {
+    /// ID of the deployed treasury program.
+    pub treasury_program_id: ProgramId,
+    /// ID of the token program the treasury chains to.
+    pub token_program_id: ProgramId,
+    /// Account that initialized the treasury, which the treasury ID is
+    /// derived from along with its name.
+    #[serde(with = "account_id_string")]
+    pub treasury_creator: AccountId,
+    /// Name the treasury ID is derived from.
+    #[serde(default = "default_treasury_name")]
+    pub treasury_name: String,
+    /// ID of the AMM program `set-amm-program` pins, if configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amm_program_id: Option<ProgramId>,
+    /// Path of the treasury program binary, for privacy-preserving
+    /// transactions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub treasury_bin: Option<PathBuf>,
+    /// Path of the token program binary, for privacy-preserving transactions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_bin: Option<PathBuf>,
+}
+
+impl TreasuryConfig {
+    /// Derive the program IDs from the compiled treasury, token and
+    /// (optionally) AMM binaries, for the treasury `treasury_creator` creates
+    /// as `treasury_name`.
+    pub fn from_binaries(
+        treasury_bin: &Path,
+        token_bin: &Path,
+        amm_bin: Option<&Path>,
+        treasury_creator: AccountId,
+        treasury_name: &str,
+    ) -> Result<Self> {
+        Ok(Self {
+            treasury_program_id: load_program(treasury_bin)?.id(),
+            token_program_id: load_program(token_bin)?.id(),
+            treasury_creator,
+            treasury_name: treasury_name.to_string(),
+            amm_program_id: amm_bin
+                .map(|path| load_program(path).map(|program| program.id()))
+                .transpose()?,
+            treasury_bin: Some(treasury_bin.to_path_buf()),
+            token_bin: Some(token_bin.to_path_buf()),
+        })
+    }
+
+    /// ID of the configured treasury within the deployment.
+    pub fn treasury_id(&self) -> TreasuryId {
+        TreasuryId::from_name(&self.treasury_creator, &self.treasury_name)
+    }
+
+    /// Load the treasury program with the Token program it chains to, for
+    /// proving privacy-preserving transactions. Fails if the binaries have
+    /// changed since `treasury init`.
+    pub fn programs(&self) -> Result<ProgramWithDependencies> {
+        let (Some(treasury_bin), Some(token_bin)) = (&self.treasury_bin, &self.token_bin) else {
+            bail!("no program binaries configured (run `treasury init` again)");
+        };
+        let treasury = load_program(treasury_bin)?;
+        let token = load_program(token_bin)?;
+        ensure!(
+            treasury.id() == self.treasury_program_id && token.id() == self.token_program_id,
+            "program binaries no longer match the configured program IDs"
+        );
+        Ok(ProgramWithDependencies::new(
+            treasury,
+            HashMap::from([(self.token_program_id, token)]),
+        ))
+    }
+
+    /// Read the config from a TOML file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).with_context(|| {
+            format!(
+                "failed to read config {} (run `treasury init` first)",
+                path.display()
+            )
+        })?;
+        toml::from_str(&contents).with_context(|| format!("invalid config {}", path.display()))
+    }
+
+    /// Write the config to a TOML file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = toml::to_string_pretty(self)?;
+        std::fs::write(path, contents)
+            .with_context(|| format!("failed to write config {}", path.display()))
+    }
+}
+
+fn default_treasury_name() -> String {
+    DEFAULT_TREASURY_NAME.to_string()
+}
+
+/// Store an account ID as the wallet prints it, rather than as raw bytes.
+mod account_id_string {
+    use nssa_core::account::AccountId;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(id: &AccountId, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(id)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<AccountId, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(|err| {
+            serde::de::Error::custom(format!("invalid account ID {value:?}: {err:?}"))
+        })
+    }
+}
+
+fn load_program(path: &Path) -> Result<Program> {
+    let bytecode =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Program::new(bytecode)
+        .map_err(|err| anyhow::anyhow!("invalid program binary {}: {err:?}", path.display()))
+}
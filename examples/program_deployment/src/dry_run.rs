@@ -0,0 +1,640 @@
+//! Dry runs: execute a treasury instruction natively before submitting it.
+//!
+//! The treasury program runs through `treasury_program::process` on the
+//! fetched pre-states, exactly as the guest would. Its chained calls to the
+//! Token program are executed by a small native model of the Token program,
+//! over its own `token_core` instruction and account types. Along the way the
+//! simulator applies the runtime's checks: matching pre-states, account
+//! ownership, and that every authorized account in a chained call was signed
+//! for or is a PDA of the caller.
+//!
+//! Chained calls to other programs (such as the AMM or another treasury) are
+//! checked for authorization but not executed; the dry run lists them as
+//! unsimulated and their account changes are missing from the result.
+
+use std::fmt;
+
+use anyhow::{Result, anyhow, bail, ensure};
+use nssa::AccountId;
+use nssa_core::account::{Account, AccountWithMetadata, Data};
+use nssa_core::program::{AccountPostState, ChainedCall, ProgramId};
+use token_core::TokenDefinition;
+use treasury_core::TokenHolding;
+
+use crate::client::TreasuryTx;
+use crate::config::TreasuryConfig;
+
+/// Result of a successful dry run: every account the transaction touches,
+/// before and after.
+#[derive(Debug, Clone)]
+pub struct DryRun {
+    pub changes: Vec<AccountChange>,
+    /// Programs whose chained calls could not be simulated.
+    pub unsimulated: Vec<ProgramId>,
+}
+
+impl DryRun {
+    /// Only the accounts whose state changes.
+    pub fn changed(&self) -> impl Iterator<Item = &AccountChange> {
+        self.changes.iter().filter(|change| change.is_changed())
+    }
+}
+
+/// One account's state before and after the transaction.
+#[derive(Debug, Clone)]
+pub struct AccountChange {
+    pub account_id: AccountId,
+    pub before: Account,
+    pub after: Account,
+}
+
+impl AccountChange {
+    pub fn is_changed(&self) -> bool {
+        self.before != self.after
+    }
+
+    /// Token balance before the transaction, if the account is a token holding.
+    pub fn token_balance_before(&self) -> Option<u128> {
+        TokenHolding::decode(&self.before.data).map(|holding| holding.balance)
+    }
+
+    /// Token balance after the transaction, if the account is a token holding.
+    pub fn token_balance_after(&self) -> Option<u128> {
+        TokenHolding::decode(&self.after.data).map(|holding| holding.balance)
+    }
+}
+
+impl fmt::Display for DryRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let changed: Vec<_> = self.changed().collect();
+        write!(f, "Dry run: {} account(s) change", changed.len())?;
+        for change in changed {
+            write!(f, "\n  {}", change.account_id)?;
+            if change.before.program_owner != change.after.program_owner {
+                write!(f, "\n    owner:          {:?}", change.after.program_owner)?;
+            }
+            if change.before.balance != change.after.balance {
+                write!(
+                    f,
+                    "\n    balance:        {} → {}",
+                    change.before.balance, change.after.balance
+                )?;
+            }
+            let (before, after) = (change.token_balance_before(), change.token_balance_after());
+            if before.is_some() || after.is_some() {
+                write!(
+                    f,
+                    "\n    token balance:  {} → {}",
+                    before.unwrap_or(0),
+                    after.unwrap_or(0)
+                )?;
+            }
+            if change.before.data != change.after.data {
+                write!(
+                    f,
+                    "\n    data:           {} → {} bytes",
+                    change.before.data.len(),
+                    change.after.data.len()
+                )?;
+            }
+        }
+        for program_id in &self.unsimulated {
+            write!(f, "\n  (not simulated: chained call to program {program_id:?})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Run `tx` against `pre_accounts` (one per `tx.account_ids`, in order).
+///
+/// Fails if the treasury program or a chained Token call would reject the
+/// transaction.
+pub fn simulate(
+    config: &TreasuryConfig,
+    tx: &TreasuryTx,
+    pre_accounts: Vec<Account>,
+) -> Result<DryRun> {
+    ensure!(
+        pre_accounts.len() == tx.account_ids.len(),
+        "expected {} pre-states, got {}",
+        tx.account_ids.len(),
+        pre_accounts.len()
+    );
+
+    let pre_states: Vec<AccountWithMetadata> = tx
+        .account_ids
+        .iter()
+        .zip(&pre_accounts)
+        .map(|(account_id, account)| {
+            AccountWithMetadata::new(account.clone(), tx.signers.contains(account_id), *account_id)
+        })
+        .collect();
+
+    let mut ledger = Ledger {
+        accounts: tx.account_ids.iter().copied().zip(pre_accounts.clone()).collect(),
+        signers: &tx.signers,
+        token_program_id: config.token_program_id,
+        unsimulated: vec![],
+    };
+
+    let mut accounts = pre_states.clone();
+    let output = treasury_program::process(&mut accounts, &tx.instruction)
+        .map_err(|err| anyhow!("treasury program would reject the transaction: {err}"))?;
+    ledger.apply(&config.treasury_program_id, &pre_states, &output.post_states)?;
+    for call in &output.chained_calls {
+        ledger.execute_chained_call(&config.treasury_program_id, call)?;
+    }
+
+    let changes = tx
+        .account_ids
+        .iter()
+        .zip(pre_accounts)
+        .map(|(account_id, before)| AccountChange {
+            account_id: *account_id,
+            after: ledger.get(account_id).cloned().unwrap_or_default(),
+            before,
+        })
+        .collect();
+    Ok(DryRun {
+        changes,
+        unsimulated: ledger.unsimulated,
+    })
+}
+
+/// Account states as the transaction executes.
+struct Ledger<'a> {
+    accounts: Vec<(AccountId, Account)>,
+    signers: &'a [AccountId],
+    token_program_id: ProgramId,
+    unsimulated: Vec<ProgramId>,
+}
+
+impl Ledger<'_> {
+    fn get(&self, account_id: &AccountId) -> Option<&Account> {
+        self.accounts
+            .iter()
+            .find(|(id, _)| id == account_id)
+            .map(|(_, account)| account)
+    }
+
+    fn set(&mut self, account_id: AccountId, account: Account) {
+        match self.accounts.iter_mut().find(|(id, _)| *id == account_id) {
+            Some((_, existing)) => *existing = account,
+            None => self.accounts.push((account_id, account)),
+        }
+    }
+
+    /// Apply a program's post-states, checking the runtime's ownership rules.
+    fn apply(
+        &mut self,
+        program_id: &ProgramId,
+        pre_states: &[AccountWithMetadata],
+        post_states: &[AccountPostState],
+    ) -> Result<()> {
+        ensure!(
+            pre_states.len() == post_states.len(),
+            "program returned {} post-states for {} accounts",
+            post_states.len(),
+            pre_states.len()
+        );
+        for (pre, post) in pre_states.iter().zip(post_states) {
+            let current = self.get(&pre.account_id).cloned().unwrap_or_default();
+            ensure!(
+                current == pre.account,
+                "stale pre-state for account {}",
+                pre.account_id
+            );
+            let mut account = post.account().clone();
+            if post.requires_claim() {
+                ensure!(
+                    pre.account == Account::default(),
+                    "account {} is already claimed",
+                    pre.account_id
+                );
+                account.program_owner = *program_id;
+            } else if account != pre.account {
+                ensure!(
+                    pre.account.program_owner == *program_id,
+                    "program does not own modified account {}",
+                    pre.account_id
+                );
+            }
+            self.set(pre.account_id, account);
+        }
+        Ok(())
+    }
+
+    fn execute_chained_call(&mut self, caller_id: &ProgramId, call: &ChainedCall) -> Result<()> {
+        for pre in &call.pre_states {
+            if pre.is_authorized {
+                let signed = self.signers.contains(&pre.account_id);
+                let derived = call
+                    .pda_seeds
+                    .iter()
+                    .any(|seed| AccountId::from((caller_id, seed)) == pre.account_id);
+                ensure!(
+                    signed || derived,
+                    "chained call marks {} as authorized without a signature or PDA seed",
+                    pre.account_id
+                );
+            }
+        }
+        if call.program_id != self.token_program_id {
+            self.unsimulated.push(call.program_id);
+            return Ok(());
+        }
+        let post_states = token::execute(&call.instruction_data, &call.pre_states)?;
+        let token_program_id = self.token_program_id;
+        self.apply(&token_program_id, &call.pre_states, &post_states)
+    }
+}
+
+/// Native model of the Token program instructions the treasury chains to.
+mod token {
+    use super::*;
+
+    pub fn execute(
+        instruction_data: &[u32],
+        pre_states: &[AccountWithMetadata],
+    ) -> Result<Vec<AccountPostState>> {
+        let instruction: token_core::Instruction =
+            risc0_zkvm::serde::from_slice(instruction_data)
+                .map_err(|err| anyhow!("malformed token instruction: {err}"))?;
+
+        match instruction {
+            token_core::Instruction::NewFungibleDefinition { name, total_supply } => {
+                new_definition(pre_states, total_supply, name)
+            }
+            token_core::Instruction::Transfer { amount_to_transfer } => {
+                transfer(pre_states, amount_to_transfer)
+            }
+            token_core::Instruction::Burn { amount_to_burn } => burn(pre_states, amount_to_burn),
+            token_core::Instruction::Mint { amount_to_mint } => mint(pre_states, amount_to_mint),
+            other => bail!("cannot simulate token instruction {other:?}"),
+        }
+    }
+
+    fn new_definition(
+        pre_states: &[AccountWithMetadata],
+        total_supply: u128,
+        name: String,
+    ) -> Result<Vec<AccountPostState>> {
+        let [definition, holding] = pre_states else {
+            bail!("token NewFungibleDefinition expects 2 accounts");
+        };
+        ensure!(
+            definition.account == Account::default() && holding.account == Account::default(),
+            "token NewFungibleDefinition needs uninitialized accounts"
+        );
+        let definition_data = Data::from(&TokenDefinition::Fungible {
+            name,
+            total_supply,
+            metadata_id: None,
+        });
+        let holding_data = TokenHolding {
+            definition_id: definition.account_id,
+            balance: total_supply,
+        }
+        .encode();
+        Ok(vec![
+            AccountPostState::new_claimed(with_data(&definition.account, definition_data)),
+            AccountPostState::new_claimed(with_data(&holding.account, holding_data)),
+        ])
+    }
+
+    fn transfer(pre_states: &[AccountWithMetadata], amount: u128) -> Result<Vec<AccountPostState>> {
+        let [sender, recipient] = pre_states else {
+            bail!("token Transfer expects 2 accounts");
+        };
+        ensure!(sender.is_authorized, "token Transfer sender is not authorized");
+        let sender_holding = TokenHolding::decode(&sender.account.data)
+            .ok_or_else(|| anyhow!("token Transfer sender {} is not a holding", sender.account_id))?;
+        let remaining = sender_holding.balance.checked_sub(amount).ok_or_else(|| {
+            anyhow!(
+                "insufficient balance: {} holds {}, transfer needs {amount}",
+                sender.account_id,
+                sender_holding.balance
+            )
+        })?;
+
+        let recipient_is_new = recipient.account == Account::default();
+        let recipient_balance = if recipient_is_new {
+            0
+        } else {
+            let holding = TokenHolding::decode(&recipient.account.data).ok_or_else(|| {
+                anyhow!("token Transfer recipient {} is not a holding", recipient.account_id)
+            })?;
+            ensure!(
+                holding.definition_id == sender_holding.definition_id,
+                "token Transfer between different definitions"
+            );
+            holding.balance
+        };
+        let recipient_balance = recipient_balance
+            .checked_add(amount)
+            .ok_or_else(|| anyhow!("token Transfer overflows recipient balance"))?;
+
+        let sender_post = with_data(
+            &sender.account,
+            TokenHolding {
+                balance: remaining,
+                ..sender_holding
+            }
+            .encode(),
+        );
+        let recipient_post = with_data(
+            &recipient.account,
+            TokenHolding {
+                definition_id: sender_holding.definition_id,
+                balance: recipient_balance,
+            }
+            .encode(),
+        );
+        Ok(vec![
+            AccountPostState::new(sender_post),
+            if recipient_is_new {
+                AccountPostState::new_claimed(recipient_post)
+            } else {
+                AccountPostState::new(recipient_post)
+            },
+        ])
+    }
+
+    fn burn(pre_states: &[AccountWithMetadata], amount: u128) -> Result<Vec<AccountPostState>> {
+        let [definition, holding] = pre_states else {
+            bail!("token Burn expects 2 accounts");
+        };
+        ensure!(holding.is_authorized, "token Burn holding is not authorized");
+        let mut token_definition = fungible_definition(definition)?;
+        let token_holding = TokenHolding::decode(&holding.account.data)
+            .ok_or_else(|| anyhow!("{} is not a token holding", holding.account_id))?;
+        ensure!(
+            token_holding.definition_id == definition.account_id,
+            "token Burn holding belongs to another definition"
+        );
+        let balance = token_holding.balance.checked_sub(amount).ok_or_else(|| {
+            anyhow!(
+                "insufficient balance: {} holds {}, burn needs {amount}",
+                holding.account_id,
+                token_holding.balance
+            )
+        })?;
+        if let TokenDefinition::Fungible { total_supply, .. } = &mut token_definition {
+            *total_supply = total_supply
+                .checked_sub(amount)
+                .ok_or_else(|| anyhow!("token Burn exceeds total supply"))?;
+        }
+
+        let definition_post = with_data(&definition.account, Data::from(&token_definition));
+        let holding_post = with_data(
+            &holding.account,
+            TokenHolding {
+                balance,
+                ..token_holding
+            }
+            .encode(),
+        );
+        Ok(vec![
+            AccountPostState::new(definition_post),
+            AccountPostState::new(holding_post),
+        ])
+    }
+
+    fn mint(pre_states: &[AccountWithMetadata], amount: u128) -> Result<Vec<AccountPostState>> {
+        let [definition, holding] = pre_states else {
+            bail!("token Mint expects 2 accounts");
+        };
+        ensure!(definition.is_authorized, "token Mint definition is not authorized");
+        let mut token_definition = fungible_definition(definition)?;
+        if let TokenDefinition::Fungible { total_supply, .. } = &mut token_definition {
+            *total_supply = total_supply
+                .checked_add(amount)
+                .ok_or_else(|| anyhow!("token Mint overflows total supply"))?;
+        }
+
+        let holding_is_new = holding.account == Account::default();
+        let balance = if holding_is_new {
+            0
+        } else {
+            let token_holding = TokenHolding::decode(&holding.account.data)
+                .ok_or_else(|| anyhow!("{} is not a token holding", holding.account_id))?;
+            ensure!(
+                token_holding.definition_id == definition.account_id,
+                "token Mint holding belongs to another definition"
+            );
+            token_holding.balance
+        };
+        let balance = balance
+            .checked_add(amount)
+            .ok_or_else(|| anyhow!("token Mint overflows holding balance"))?;
+
+        let definition_post = with_data(&definition.account, Data::from(&token_definition));
+        let holding_post = with_data(
+            &holding.account,
+            TokenHolding {
+                definition_id: definition.account_id,
+                balance,
+            }
+            .encode(),
+        );
+        Ok(vec![
+            AccountPostState::new(definition_post),
+            if holding_is_new {
+                AccountPostState::new_claimed(holding_post)
+            } else {
+                AccountPostState::new(holding_post)
+            },
+        ])
+    }
+
+    fn fungible_definition(definition: &AccountWithMetadata) -> Result<TokenDefinition> {
+        match TokenDefinition::try_from(&definition.account.data) {
+            Ok(token_definition @ TokenDefinition::Fungible { .. }) => Ok(token_definition),
+            _ => bail!("{} is not a fungible token definition", definition.account_id),
+        }
+    }
+
+    fn with_data(account: &Account, data: Data) -> Account {
+        let mut account = account.clone();
+        account.data = data;
+        account
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use treasury_core::{
+        Instruction, LedgerEntry, LedgerKind, TreasuryId, TreasuryState, VaultRecord,
+        compute_ledger_page_pda, compute_share_definition_pda, compute_treasury_state_pda,
+        compute_vault_holding_pda,
+    };
+
+    use super::*;
+
+    const TREASURY_PROGRAM_ID: ProgramId = [1; 8];
+    const TOKEN_PROGRAM_ID: ProgramId = [2; 8];
+
+    fn config() -> TreasuryConfig {
+        TreasuryConfig {
+            treasury_program_id: TREASURY_PROGRAM_ID,
+            token_program_id: TOKEN_PROGRAM_ID,
+            treasury_creator: admin_id(),
+            treasury_name: "test".to_string(),
+            amm_program_id: None,
+            treasury_bin: None,
+            token_bin: None,
+        }
+    }
+
+    fn treasury() -> TreasuryId {
+        config().treasury_id()
+    }
+
+    fn definition_id() -> AccountId {
+        AccountId::new([7; 32])
+    }
+
+    fn admin_id() -> AccountId {
+        AccountId::new([9; 32])
+    }
+
+    fn vault_id() -> AccountId {
+        compute_vault_holding_pda(&TREASURY_PROGRAM_ID, &treasury(), &definition_id())
+    }
+
+    /// A treasury with one vault, for [`definition_id`].
+    fn state_account() -> Account {
+        let state = TreasuryState {
+            vault_count: 1,
+            vaults: vec![VaultRecord::new(definition_id(), vault_id())],
+            admin: Some(admin_id()),
+            treasury_id: treasury(),
+            ..TreasuryState::default()
+        };
+        Account {
+            program_owner: TREASURY_PROGRAM_ID,
+            data: borsh::to_vec(&state).unwrap().try_into().unwrap(),
+            ..Account::default()
+        }
+    }
+
+    fn holding_account(balance: u128) -> Account {
+        Account {
+            program_owner: TOKEN_PROGRAM_ID,
+            data: TokenHolding {
+                definition_id: definition_id(),
+                balance,
+            }
+            .encode(),
+            ..Account::default()
+        }
+    }
+
+    /// Simulate `tx`, standing in for the sequencer, and return the ledger
+    /// entry it appends to the last account.
+    fn ledger_entry(tx: &TreasuryTx, pre_accounts: Vec<Account>) -> (DryRun, LedgerEntry) {
+        let dry_run = simulate(&config(), tx, pre_accounts).unwrap();
+        let page = &dry_run.changes.last().unwrap().after;
+        let entry = LedgerEntry::decode(&page.data).unwrap();
+        (dry_run, entry)
+    }
+
+    /// A Send of 100 from a vault holding 1000 to a fresh recipient.
+    fn send(recipient_id: AccountId, private_recipient: bool) -> LedgerEntry {
+        let tx = TreasuryTx {
+            account_ids: vec![
+                compute_treasury_state_pda(&TREASURY_PROGRAM_ID, &treasury()),
+                vault_id(),
+                recipient_id,
+                admin_id(),
+                compute_ledger_page_pda(&TREASURY_PROGRAM_ID, &treasury(), 0),
+            ],
+            instruction: Instruction::Send {
+                amount: 100,
+                token_program_id: TOKEN_PROGRAM_ID,
+                memo: Some("INV-1".to_string()),
+                private_recipient,
+            },
+            signers: vec![admin_id()],
+        };
+        let pre_accounts = vec![
+            state_account(),
+            holding_account(1000),
+            Account::default(),
+            Account::default(),
+            Account::default(),
+        ];
+        let (dry_run, entry) = ledger_entry(&tx, pre_accounts);
+
+        let recipient = TokenHolding::decode(&dry_run.changes[2].after.data).unwrap();
+        assert_eq!(recipient.balance, 100);
+        assert_eq!(entry.kind, LedgerKind::Send);
+        assert_eq!(entry.vault, vault_id());
+        assert_eq!(entry.amount, 100);
+        assert_eq!(entry.memo.as_deref(), Some("INV-1"));
+        entry
+    }
+
+    /// A first Deposit of 100 into a vault holding 1000.
+    fn deposit(sender_id: AccountId, private_sender: bool) -> LedgerEntry {
+        let share_holding_id = AccountId::new([10; 32]);
+        let tx = TreasuryTx {
+            account_ids: vec![
+                compute_treasury_state_pda(&TREASURY_PROGRAM_ID, &treasury()),
+                sender_id,
+                vault_id(),
+                compute_share_definition_pda(&TREASURY_PROGRAM_ID, &treasury(), &definition_id()),
+                share_holding_id,
+                compute_ledger_page_pda(&TREASURY_PROGRAM_ID, &treasury(), 0),
+            ],
+            instruction: Instruction::Deposit {
+                amount: 100,
+                token_program_id: TOKEN_PROGRAM_ID,
+                memo: None,
+                private_sender,
+            },
+            signers: vec![sender_id, share_holding_id],
+        };
+        let pre_accounts = vec![
+            state_account(),
+            holding_account(500),
+            holding_account(1000),
+            Account::default(),
+            Account::default(),
+            Account::default(),
+        ];
+        let (dry_run, entry) = ledger_entry(&tx, pre_accounts);
+
+        let vault = TokenHolding::decode(&dry_run.changes[2].after.data).unwrap();
+        assert_eq!(vault.balance, 1100);
+        let shares = TokenHolding::decode(&dry_run.changes[4].after.data).unwrap();
+        assert_eq!(shares.balance, 100);
+        assert_eq!(entry.kind, LedgerKind::Deposit);
+        assert_eq!(entry.amount, 100);
+        entry
+    }
+
+    #[test]
+    fn send_records_the_recipient() {
+        let recipient_id = AccountId::new([8; 32]);
+        assert_eq!(send(recipient_id, false).counterparty, recipient_id);
+    }
+
+    #[test]
+    fn private_send_records_a_zero_counterparty() {
+        let recipient_id = AccountId::new([8; 32]);
+        assert_eq!(send(recipient_id, true).counterparty, AccountId::default());
+    }
+
+    #[test]
+    fn deposit_records_the_sender() {
+        let sender_id = AccountId::new([11; 32]);
+        assert_eq!(deposit(sender_id, false).counterparty, sender_id);
+    }
+
+    #[test]
+    fn private_deposit_records_a_zero_counterparty() {
+        let sender_id = AccountId::new([11; 32]);
+        assert_eq!(deposit(sender_id, true).counterparty, AccountId::default());
+    }
+}
@@ -0,0 +1,13 @@
+//! Off-chain helpers for the Treasury program.
+//!
+//! Shared by the `treasury` CLI: loading program IDs from a config file,
+//! building the account lists each instruction expects, dry-running and
+//! submitting transactions through the wallet's sequencer client (publicly, or
+//! proved as privacy-preserving transactions), and reading back the treasury
+//! state and vault balances.
+
+pub mod client;
+pub mod config;
+pub mod dry_run;
+pub mod private;
+pub mod query;
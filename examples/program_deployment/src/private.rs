@@ -0,0 +1,172 @@
+//! Privacy-preserving transactions: treasury instructions on private accounts.
+//!
+//! A private account exists on-chain only as a commitment. To touch one, the
+//! wallet executes the treasury program (and the Token calls it chains to)
+//! inside the privacy circuit and submits the proof, the new commitments and
+//! the encrypted account states instead of a public transaction. The program
+//! sees private accounts like any other, under their account IDs, so the
+//! instructions themselves are unchanged, except that Send and Deposit are
+//! told to leave a private counterparty out of the public ledger.
+//!
+//! Proving needs the program binaries, not just their IDs; see
+//! [`TreasuryConfig::programs`](crate::config::TreasuryConfig::programs).
+
+use anyhow::{Context, Result, anyhow};
+use nssa::AccountId;
+use nssa::program::Program;
+use nssa_core::account::Account;
+use treasury_core::Instruction;
+use wallet::privacy_preserving_tx::PrivacyPreservingAccount;
+
+use crate::client::{TreasuryClient, TreasuryTx};
+use crate::dry_run::{self, DryRun};
+
+/// A treasury transaction with some of its accounts private.
+#[derive(Debug, Clone)]
+pub struct PrivateTx {
+    /// The transaction as the program sees it. Private accounts appear under
+    /// their account IDs, which is what dry runs execute. The wallet's own
+    /// private accounts among the signers are authorized inside the circuit
+    /// with their private keys instead of signing.
+    pub tx: TreasuryTx,
+    /// How each account enters the circuit, in the same order.
+    pub accounts: Vec<PrivacyPreservingAccount>,
+}
+
+impl PrivateTx {
+    /// Make the accounts at the given positions private; the rest stay
+    /// public.
+    fn new(
+        tx: TreasuryTx,
+        private: impl IntoIterator<Item = (usize, PrivacyPreservingAccount)>,
+    ) -> Self {
+        let mut accounts: Vec<_> = tx
+            .account_ids
+            .iter()
+            .map(|account_id| PrivacyPreservingAccount::Public(*account_id))
+            .collect();
+        for (position, account) in private {
+            accounts[position] = account;
+        }
+        Self { tx, accounts }
+    }
+}
+
+/// The account ID a program sees for an account. A foreign private account
+/// is identified by its owner's nullifier public key.
+pub fn account_id(account: &PrivacyPreservingAccount) -> AccountId {
+    match account {
+        PrivacyPreservingAccount::Public(account_id)
+        | PrivacyPreservingAccount::PrivateOwned(account_id) => *account_id,
+        PrivacyPreservingAccount::PrivateForeign { npk, .. } => AccountId::from(npk),
+    }
+}
+
+impl TreasuryClient {
+    /// Send tokens from the vault to a private recipient: either one of the
+    /// wallet's own private accounts, or someone else's, identified by their
+    /// nullifier and viewing public keys.
+    ///
+    /// Accounts as for [`TreasuryClient::send_tx`], with the recipient
+    /// private. The admin stays public and signs. The memo is public: it is
+    /// recorded in the ledger. A private recipient is recorded in the ledger
+    /// as a zero counterparty.
+    pub fn private_send_tx(
+        &self,
+        token_definition_id: AccountId,
+        recipient: PrivacyPreservingAccount,
+        admin_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) -> PrivateTx {
+        let recipient_id = account_id(&recipient);
+        let mut tx = self.send_tx(token_definition_id, recipient_id, admin_id, amount, memo);
+        if let Instruction::Send {
+            private_recipient, ..
+        } = &mut tx.instruction
+        {
+            *private_recipient = !matches!(recipient, PrivacyPreservingAccount::Public(_));
+        }
+        PrivateTx::new(tx, [(2, recipient)])
+    }
+
+    /// Deposit tokens from one of the wallet's private accounts into the
+    /// vault, so the deposit can't be traced to the donor's public accounts.
+    /// The shares go to another of the wallet's private accounts: a public
+    /// share holding would have to sign, which would link the deposit to it.
+    ///
+    /// Accounts as for [`TreasuryClient::deposit_tx`], with the sender and
+    /// share holding private. The ledger records a zero counterparty instead
+    /// of the sender. The memo is public: it is recorded in the ledger.
+    pub fn private_deposit_tx(
+        &self,
+        token_definition_id: AccountId,
+        sender_id: AccountId,
+        share_holding_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    ) -> PrivateTx {
+        let mut tx =
+            self.deposit_tx(token_definition_id, sender_id, share_holding_id, amount, memo);
+        if let Instruction::Deposit { private_sender, .. } = &mut tx.instruction {
+            *private_sender = true;
+        }
+        PrivateTx::new(
+            tx,
+            [
+                (1, PrivacyPreservingAccount::PrivateOwned(sender_id)),
+                (4, PrivacyPreservingAccount::PrivateOwned(share_holding_id)),
+            ],
+        )
+    }
+
+    /// Execute a private transaction locally without submitting it. The
+    /// wallet's own private accounts are read from its local storage; foreign
+    /// private accounts are taken to be new.
+    pub async fn dry_run_private(&self, tx: &PrivateTx) -> Result<DryRun> {
+        let tx = self.with_private_ledger_page(tx).await?;
+        self.simulate_private(&tx).await
+    }
+
+    /// Dry-run a private transaction, then prove and submit it if the program
+    /// would accept it. Returns the simulated account changes.
+    pub async fn submit_private(&self, tx: &PrivateTx) -> Result<DryRun> {
+        let tx = self.with_private_ledger_page(tx).await?;
+        let simulated = self.simulate_private(&tx).await?;
+
+        let programs = self.config().programs()?;
+        let instruction_data = Program::serialize_instruction(&tx.tx.instruction)
+            .map_err(|err| anyhow!("failed to encode instruction: {err:?}"))?;
+        self.wallet()
+            .send_privacy_preserving_tx(tx.accounts, &instruction_data, &programs)
+            .await
+            .map_err(|err| anyhow!("failed to prove or submit private transaction: {err:?}"))?;
+        Ok(simulated)
+    }
+
+    /// `tx` with the current ledger page appended, as a public account.
+    async fn with_private_ledger_page(&self, tx: &PrivateTx) -> Result<PrivateTx> {
+        let ledger_page_id = self.current_ledger_page_id().await?;
+        let mut tx = tx.clone();
+        tx.tx.account_ids.push(ledger_page_id);
+        tx.accounts.push(PrivacyPreservingAccount::Public(ledger_page_id));
+        Ok(tx)
+    }
+
+    async fn simulate_private(&self, tx: &PrivateTx) -> Result<DryRun> {
+        let mut pre_accounts = Vec::with_capacity(tx.accounts.len());
+        for account in &tx.accounts {
+            pre_accounts.push(match account {
+                PrivacyPreservingAccount::Public(account_id) => {
+                    self.fetch_account(*account_id).await?
+                }
+                PrivacyPreservingAccount::PrivateOwned(account_id) => self
+                    .wallet()
+                    .get_account_private(account_id)
+                    .with_context(|| format!("no private account {account_id} in the wallet"))?,
+                PrivacyPreservingAccount::PrivateForeign { .. } => Account::default(),
+            });
+        }
+        dry_run::simulate(self.config(), &tx.tx, pre_accounts)
+    }
+}
@@ -0,0 +1,483 @@
+//! Read-side queries: decode what the treasury holds.
+//!
+//! The treasury state PDA is decoded with borsh, and each registered vault's
+//! holding account is decoded with the Token program's holding layout.
+//! Payment schedules, escrows, grants, invoices and the treasury metadata are
+//! decoded from their own PDAs, and the ledger from its pages.
+
+use anyhow::{Context, Result, bail};
+use borsh::BorshDeserialize;
+use nssa::AccountId;
+use nssa_core::program::ProgramId;
+use serde::Serialize;
+use treasury_core::{
+    Escrow, Grant, GrantProgress, Invoice, LEDGER_PAGE_ENTRIES, LedgerEntry, LedgerKind, Schedule,
+    TokenHolding, TreasuryMetadata, TreasuryState, VaultRecord, clock_account_id, ledger_entries,
+    read_clock,
+};
+
+use crate::client::TreasuryClient;
+
+/// Everything the treasury holds, read in one pass.
+#[derive(Debug, Clone)]
+pub struct TreasurySnapshot {
+    pub treasury_program_id: ProgramId,
+    pub token_program_id: ProgramId,
+    pub treasury_state_id: AccountId,
+    /// The decoded treasury state (counters and configuration).
+    pub state: TreasuryState,
+    /// One entry per registered vault, in registry order.
+    pub vaults: Vec<VaultSnapshot>,
+}
+
+impl TreasurySnapshot {
+    /// Look up a vault by its token definition.
+    pub fn vault(&self, token_definition_id: &AccountId) -> Option<&VaultSnapshot> {
+        self.vaults
+            .iter()
+            .find(|vault| vault.record.token_definition_id == *token_definition_id)
+    }
+}
+
+/// A vault's registry entry and what its accounts currently hold.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VaultSnapshot {
+    /// The registry entry, or a fresh one if the vault is not registered.
+    #[serde(flatten)]
+    pub record: VaultRecord,
+    /// Whether the vault is listed in the treasury's vault registry.
+    pub registered: bool,
+    /// Token balance, or `None` if the holding account is not initialized.
+    pub balance: Option<u128>,
+    /// The vault's share definition PDA.
+    pub share_definition_id: AccountId,
+    /// The labeled vaults receiving split deposits, in target order.
+    pub split_vaults: Vec<LabeledVaultSnapshot>,
+}
+
+/// A labeled vault holding and its balance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LabeledVaultSnapshot {
+    pub label: String,
+    pub holding_id: AccountId,
+    /// Token balance, or `None` if the holding account is not initialized.
+    pub balance: Option<u128>,
+}
+
+/// A payment schedule and its PDA.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleSnapshot {
+    /// Index of the schedule within its vault.
+    pub index: u64,
+    pub schedule_id: AccountId,
+    pub schedule: Schedule,
+}
+
+/// An escrow, its PDAs and the balance of its holding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscrowSnapshot {
+    /// Escrow ID.
+    pub index: u64,
+    pub escrow_id: AccountId,
+    pub holding_id: AccountId,
+    pub escrow: Escrow,
+    /// Balance of the escrow holding, or `None` if it is not initialized.
+    pub balance: Option<u128>,
+}
+
+/// An invoice and its PDA.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvoiceSnapshot {
+    /// Invoice ID.
+    pub index: u64,
+    pub invoice_id: AccountId,
+    pub invoice: Invoice,
+}
+
+/// A grant, its PDAs and how far it has been paid out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrantSnapshot {
+    /// Grant ID.
+    pub index: u64,
+    pub grant_id: AccountId,
+    pub holding_id: AccountId,
+    pub grant: Grant,
+    pub progress: GrantProgress,
+}
+
+/// What the ledger says moved into and out of one vault holding, for
+/// reconciling against its balance.
+///
+/// `VaultRecord.deposited` and `sent` only count Deposit and Send; this sums
+/// every kind that moves the holding's funds. Some entries don't record what
+/// the holding received: swaps and liquidity changes settle in the AMM,
+/// RedeemFromTreasury records shares rather than the payout, and DepositSplit
+/// divides its amount across the labeled vaults. Those are counted in
+/// `unpriced`. Swap output paid into a vault is not in its entries at all.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct VaultFlows {
+    pub inflow: u128,
+    pub outflow: u128,
+    /// Entries touching the holding whose amount the ledger doesn't record.
+    pub unpriced: u64,
+}
+
+impl VaultFlows {
+    /// Sum the entries of `ledger` that move funds into or out of
+    /// `holding_id`.
+    pub fn from_ledger(ledger: &[LedgerEntry], holding_id: &AccountId) -> Self {
+        let mut flows = Self::default();
+        for entry in ledger {
+            let is_vault = entry.vault == *holding_id;
+            match entry.kind {
+                LedgerKind::CreateVault
+                | LedgerKind::Deposit
+                | LedgerKind::Refund
+                | LedgerKind::Reclaim
+                | LedgerKind::CancelGrant
+                | LedgerKind::MintMore
+                | LedgerKind::PayInvoice
+                    if is_vault =>
+                {
+                    flows.inflow = flows.inflow.saturating_add(entry.amount);
+                }
+                LedgerKind::Send
+                | LedgerKind::Redeem
+                | LedgerKind::ExecuteDue
+                | LedgerKind::CreateEscrow
+                | LedgerKind::CreateGrant
+                | LedgerKind::Burn
+                | LedgerKind::Swap
+                | LedgerKind::FundTreasury
+                    if is_vault =>
+                {
+                    flows.outflow = flows.outflow.saturating_add(entry.amount);
+                }
+                LedgerKind::InternalTransfer if is_vault => {
+                    flows.outflow = flows.outflow.saturating_add(entry.amount);
+                }
+                LedgerKind::InternalTransfer if entry.counterparty == *holding_id => {
+                    flows.inflow = flows.inflow.saturating_add(entry.amount);
+                }
+                LedgerKind::AddLiquidity
+                | LedgerKind::RemoveLiquidity
+                | LedgerKind::RedeemFromTreasury
+                | LedgerKind::DepositSplit
+                    if is_vault =>
+                {
+                    flows.unpriced += 1;
+                }
+                _ => {}
+            }
+        }
+        flows
+    }
+
+    /// The balance these flows account for, if every entry was priced and
+    /// more came in than went out.
+    pub fn expected_balance(&self) -> Option<u128> {
+        if self.unpriced > 0 {
+            return None;
+        }
+        self.inflow.checked_sub(self.outflow)
+    }
+}
+
+impl TreasuryClient {
+    /// Fetch the treasury state and the balance of every registered vault.
+    pub async fn snapshot(&self) -> Result<TreasurySnapshot> {
+        let state = self.fetch_state().await?;
+        let mut vaults = Vec::with_capacity(state.vaults.len());
+        for record in &state.vaults {
+            vaults.push(self.fetch_vault(record, true).await?);
+        }
+        Ok(TreasurySnapshot {
+            treasury_program_id: self.config().treasury_program_id,
+            token_program_id: self.config().token_program_id,
+            treasury_state_id: self.treasury_state_id(),
+            state,
+            vaults,
+        })
+    }
+
+    /// Fetch a single vault by token definition, registered or not.
+    pub async fn vault_snapshot(&self, token_definition_id: AccountId) -> Result<VaultSnapshot> {
+        let state = self.fetch_state().await?;
+        match state.vault(&token_definition_id) {
+            Some(record) => self.fetch_vault(record, true).await,
+            None => {
+                let record = VaultRecord::new(
+                    token_definition_id,
+                    self.vault_holding_id(&token_definition_id),
+                );
+                self.fetch_vault(&record, false).await
+            }
+        }
+    }
+
+    /// Fetch a vault's `index`-th payment schedule.
+    pub async fn schedule(
+        &self,
+        token_definition_id: AccountId,
+        index: u64,
+    ) -> Result<ScheduleSnapshot> {
+        let schedule_id = self.schedule_id(&token_definition_id, index);
+        let account = self.fetch_account(schedule_id).await?;
+        if account.data.is_empty() {
+            bail!("schedule {index} of {token_definition_id} does not exist");
+        }
+        let schedule = Schedule::try_from_slice(&account.data)
+            .with_context(|| format!("failed to decode schedule {schedule_id}"))?;
+        Ok(ScheduleSnapshot {
+            index,
+            schedule_id,
+            schedule,
+        })
+    }
+
+    /// Fetch every payment schedule created for a vault, in creation order.
+    pub async fn schedules(&self, token_definition_id: AccountId) -> Result<Vec<ScheduleSnapshot>> {
+        let state = self.fetch_state().await?;
+        let Some(vault) = state.vault(&token_definition_id) else {
+            bail!("no vault registered for {token_definition_id}");
+        };
+        let mut schedules = Vec::with_capacity(vault.schedule_count as usize);
+        for index in 0..vault.schedule_count {
+            schedules.push(self.schedule(token_definition_id, index).await?);
+        }
+        Ok(schedules)
+    }
+
+    /// Fetch an escrow and its holding balance.
+    pub async fn escrow(&self, index: u64) -> Result<EscrowSnapshot> {
+        let escrow_id = self.escrow_id(index);
+        let account = self.fetch_account(escrow_id).await?;
+        if account.data.is_empty() {
+            bail!("escrow {index} does not exist");
+        }
+        let escrow = Escrow::try_from_slice(&account.data)
+            .with_context(|| format!("failed to decode escrow {escrow_id}"))?;
+        let holding_id = self.escrow_holding_id(index);
+        let holding = self.fetch_account(holding_id).await?;
+        Ok(EscrowSnapshot {
+            index,
+            escrow_id,
+            holding_id,
+            escrow,
+            balance: TokenHolding::decode(&holding.data).map(|holding| holding.balance),
+        })
+    }
+
+    /// Fetch every escrow the treasury has created, in ID order.
+    pub async fn escrows(&self) -> Result<Vec<EscrowSnapshot>> {
+        let escrow_count = self.fetch_state().await?.escrow_count;
+        let mut escrows = Vec::with_capacity(escrow_count as usize);
+        for index in 0..escrow_count {
+            escrows.push(self.escrow(index).await?);
+        }
+        Ok(escrows)
+    }
+
+    /// Fetch a grant and its progress.
+    pub async fn grant(&self, index: u64) -> Result<GrantSnapshot> {
+        let grant_id = self.grant_id(index);
+        let account = self.fetch_account(grant_id).await?;
+        if account.data.is_empty() {
+            bail!("grant {index} does not exist");
+        }
+        let Some(grant) = Grant::decode(&account.data) else {
+            bail!("failed to decode grant {grant_id}");
+        };
+        let Some(progress) = grant.progress() else {
+            bail!("grant {grant_id} milestone totals overflow");
+        };
+        Ok(GrantSnapshot {
+            index,
+            grant_id,
+            holding_id: self.grant_holding_id(index),
+            grant,
+            progress,
+        })
+    }
+
+    /// Fetch every grant the treasury has created, in ID order.
+    pub async fn grants(&self) -> Result<Vec<GrantSnapshot>> {
+        let grant_count = self.fetch_state().await?.grant_count;
+        let mut grants = Vec::with_capacity(grant_count as usize);
+        for index in 0..grant_count {
+            grants.push(self.grant(index).await?);
+        }
+        Ok(grants)
+    }
+
+    /// Fetch an invoice.
+    pub async fn invoice(&self, index: u64) -> Result<InvoiceSnapshot> {
+        let invoice_id = self.invoice_id(index);
+        let account = self.fetch_account(invoice_id).await?;
+        if account.data.is_empty() {
+            bail!("invoice {index} does not exist");
+        }
+        let invoice = Invoice::try_from_slice(&account.data)
+            .with_context(|| format!("failed to decode invoice {invoice_id}"))?;
+        Ok(InvoiceSnapshot {
+            index,
+            invoice_id,
+            invoice,
+        })
+    }
+
+    /// Fetch every invoice the treasury has created, in ID order.
+    pub async fn invoices(&self) -> Result<Vec<InvoiceSnapshot>> {
+        let invoice_count = self.fetch_state().await?.invoice_count;
+        let mut invoices = Vec::with_capacity(invoice_count as usize);
+        for index in 0..invoice_count {
+            invoices.push(self.invoice(index).await?);
+        }
+        Ok(invoices)
+    }
+
+    /// Fetch the treasury's metadata, or `None` if it has never been set.
+    pub async fn metadata(&self) -> Result<Option<TreasuryMetadata>> {
+        let metadata_id = self.metadata_id();
+        let account = self.fetch_account(metadata_id).await?;
+        if account.data.is_empty() {
+            return Ok(None);
+        }
+        TreasuryMetadata::try_from_slice(&account.data)
+            .map(Some)
+            .with_context(|| format!("failed to decode metadata {metadata_id}"))
+    }
+
+    /// Read the current time from the chain's clock account.
+    pub async fn clock(&self) -> Result<u64> {
+        let clock_id = clock_account_id();
+        let account = self.fetch_account(clock_id).await?;
+        read_clock(&account.data)
+            .with_context(|| format!("clock account {clock_id} does not hold a timestamp"))
+    }
+
+    /// Fetch the whole ledger, oldest entry first.
+    pub async fn ledger(&self) -> Result<Vec<LedgerEntry>> {
+        let ledger_len = self.fetch_state().await?.ledger_len;
+        let mut pages = Vec::new();
+        for page in 0..ledger_len.div_ceil(LEDGER_PAGE_ENTRIES) {
+            pages.push(self.fetch_account(self.ledger_page_id(page)).await?.data);
+        }
+        Ok(ledger_entries(pages.iter().map(|data| data.as_ref())).collect())
+    }
+
+    /// Sum the ledger's inflows and outflows for a token's vault, to
+    /// reconcile against its balance.
+    pub async fn vault_flows(&self, token_definition_id: AccountId) -> Result<VaultFlows> {
+        let ledger = self.ledger().await?;
+        Ok(VaultFlows::from_ledger(
+            &ledger,
+            &self.vault_holding_id(&token_definition_id),
+        ))
+    }
+
+    /// Fetch a token's vault labeled `label`.
+    pub async fn labeled_vault(
+        &self,
+        token_definition_id: AccountId,
+        label: &str,
+    ) -> Result<LabeledVaultSnapshot> {
+        let holding_id = self.labeled_vault_id(&token_definition_id, label);
+        let account = self.fetch_account(holding_id).await?;
+        Ok(LabeledVaultSnapshot {
+            label: label.to_string(),
+            holding_id,
+            balance: TokenHolding::decode(&account.data).map(|holding| holding.balance),
+        })
+    }
+
+    async fn fetch_vault(&self, record: &VaultRecord, registered: bool) -> Result<VaultSnapshot> {
+        let account = self.fetch_account(record.holding_id).await?;
+        let balance = if account.data.is_empty() {
+            None
+        } else {
+            let Some(holding) = TokenHolding::decode(&account.data) else {
+                bail!("vault {} is not a token holding", record.holding_id);
+            };
+            if holding.definition_id != record.token_definition_id {
+                bail!(
+                    "vault {} holds {}, expected {}",
+                    record.holding_id,
+                    holding.definition_id,
+                    record.token_definition_id
+                );
+            }
+            Some(holding.balance)
+        };
+        let mut split_vaults = Vec::new();
+        for target in record.split.iter().flat_map(|split| &split.targets) {
+            split_vaults.push(
+                self.labeled_vault(record.token_definition_id, &target.label)
+                    .await?,
+            );
+        }
+        Ok(VaultSnapshot {
+            record: record.clone(),
+            registered,
+            balance,
+            share_definition_id: self.share_definition_id(&record.token_definition_id),
+            split_vaults,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        kind: LedgerKind,
+        vault: AccountId,
+        counterparty: AccountId,
+        amount: u128,
+    ) -> LedgerEntry {
+        LedgerEntry {
+            kind,
+            vault,
+            counterparty,
+            amount,
+            seq: 0,
+            memo: None,
+        }
+    }
+
+    #[test]
+    fn flows_count_every_priced_kind_of_the_vault() {
+        let vault = AccountId::new([1; 32]);
+        let other = AccountId::new([2; 32]);
+        let ledger = [
+            entry(LedgerKind::CreateVault, vault, other, 1_000),
+            entry(LedgerKind::Deposit, vault, other, 50),
+            entry(LedgerKind::ExecuteDue, vault, other, 30),
+            entry(LedgerKind::InternalTransfer, other, vault, 20),
+            entry(LedgerKind::InternalTransfer, vault, other, 5),
+            entry(LedgerKind::Send, other, vault, 400),
+        ];
+
+        let flows = VaultFlows::from_ledger(&ledger, &vault);
+
+        assert_eq!(flows.inflow, 1_070);
+        assert_eq!(flows.outflow, 35);
+        assert_eq!(flows.expected_balance(), Some(1_035));
+    }
+
+    #[test]
+    fn unpriced_entries_leave_the_balance_unknown() {
+        let vault = AccountId::new([1; 32]);
+        let pool = AccountId::new([3; 32]);
+        let ledger = [
+            entry(LedgerKind::CreateVault, vault, pool, 1_000),
+            entry(LedgerKind::AddLiquidity, vault, pool, 10),
+        ];
+
+        let flows = VaultFlows::from_ledger(&ledger, &vault);
+
+        assert_eq!(flows.unpriced, 1);
+        assert_eq!(flows.expected_balance(), None);
+    }
+}
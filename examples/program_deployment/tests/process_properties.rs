@@ -0,0 +1,387 @@
+//! Property tests over `treasury_program::process`.
+//!
+//! Random sequences of Send, Deposit, Redeem and freezes run against one vault,
+//! each through the dry-run simulator standing in for the sequencer: it runs
+//! the handler and the Token calls it chains, and a rejected transaction
+//! changes nothing. A model of the vault predicts whether each step succeeds,
+//! and after every step the treasury must match it and its invariants hold.
+
+use std::collections::HashMap;
+
+use borsh::BorshDeserialize;
+use nssa::AccountId;
+use nssa_core::account::Account;
+use nssa_core::program::ProgramId;
+use proptest::prelude::*;
+use token_core::TokenDefinition;
+use treasury_core::{
+    Instruction, LEDGER_PAGE_ENTRIES, LedgerKind, TokenHolding, TokenMetadata, TreasuryId,
+    TreasuryState, VaultRecord, VaultStatus, compute_ledger_page_pda, compute_share_definition_pda,
+    compute_treasury_state_pda, compute_vault_holding_pda, ledger_entries, redemption_amount,
+    shares_for_deposit,
+};
+use treasury_examples::client::TreasuryTx;
+use treasury_examples::config::TreasuryConfig;
+use treasury_examples::dry_run;
+
+const TREASURY_PROGRAM_ID: ProgramId = [1; 8];
+const TOKEN_PROGRAM_ID: ProgramId = [2; 8];
+const INITIAL_SUPPLY: u128 = 10_000;
+const USERS: usize = 3;
+
+#[derive(Debug, Clone)]
+enum Op {
+    Send { user: usize, amount: u128 },
+    Deposit { user: usize, amount: u128 },
+    Redeem { user: usize, shares: u128 },
+    Freeze,
+    Unfreeze,
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        3 => (0..USERS, 0..=3_000u128).prop_map(|(user, amount)| Op::Send { user, amount }),
+        3 => (0..USERS, 0..=3_000u128).prop_map(|(user, amount)| Op::Deposit { user, amount }),
+        3 => (0..USERS, 0..=3_000u128).prop_map(|(user, shares)| Op::Redeem { user, shares }),
+        1 => Just(Op::Freeze),
+        1 => Just(Op::Unfreeze),
+    ]
+}
+
+/// Chain state: every account the treasury touches, by ID.
+struct World {
+    config: TreasuryConfig,
+    accounts: HashMap<AccountId, Account>,
+}
+
+impl World {
+    /// A treasury created by [`admin_id`], with one vault holding
+    /// [`INITIAL_SUPPLY`] of a new token.
+    fn new() -> Self {
+        let mut world = Self {
+            config: TreasuryConfig {
+                treasury_program_id: TREASURY_PROGRAM_ID,
+                token_program_id: TOKEN_PROGRAM_ID,
+                treasury_creator: admin_id(),
+                treasury_name: "props".to_string(),
+                amm_program_id: None,
+                treasury_bin: None,
+                token_bin: None,
+            },
+            accounts: HashMap::new(),
+        };
+        let tx = TreasuryTx {
+            account_ids: vec![world.state_id(), admin_id()],
+            instruction: Instruction::Initialize {
+                name: world.config.treasury_name.clone(),
+                treasury_program_id: TREASURY_PROGRAM_ID,
+            },
+            signers: vec![admin_id()],
+        };
+        assert!(world.run(tx), "Initialize must succeed");
+        let tx = TreasuryTx {
+            account_ids: vec![world.state_id(), definition_id(), world.vault_id(), admin_id()],
+            instruction: Instruction::CreateVault {
+                token_name: "PROP".to_string(),
+                metadata: TokenMetadata {
+                    display_name: "Property".to_string(),
+                    ticker: "PROP".to_string(),
+                    decimals: 0,
+                },
+                initial_supply: INITIAL_SUPPLY,
+                token_program_id: TOKEN_PROGRAM_ID,
+            },
+            signers: vec![admin_id()],
+        };
+        assert!(world.run(tx), "CreateVault must succeed");
+        world
+    }
+
+    fn treasury(&self) -> TreasuryId {
+        self.config.treasury_id()
+    }
+
+    fn state_id(&self) -> AccountId {
+        compute_treasury_state_pda(&TREASURY_PROGRAM_ID, &self.treasury())
+    }
+
+    fn vault_id(&self) -> AccountId {
+        compute_vault_holding_pda(&TREASURY_PROGRAM_ID, &self.treasury(), &definition_id())
+    }
+
+    fn share_definition_id(&self) -> AccountId {
+        compute_share_definition_pda(&TREASURY_PROGRAM_ID, &self.treasury(), &definition_id())
+    }
+
+    fn ledger_page_id(&self) -> AccountId {
+        let ledger_len = self.state().map_or(0, |state| state.ledger_len);
+        compute_ledger_page_pda(
+            &TREASURY_PROGRAM_ID,
+            &self.treasury(),
+            ledger_len / LEDGER_PAGE_ENTRIES,
+        )
+    }
+
+    fn account(&self, account_id: &AccountId) -> Account {
+        self.accounts.get(account_id).cloned().unwrap_or_default()
+    }
+
+    fn state(&self) -> Option<TreasuryState> {
+        TreasuryState::try_from_slice(&self.account(&self.state_id()).data).ok()
+    }
+
+    fn vault(&self) -> VaultRecord {
+        self.state().unwrap().vault(&definition_id()).unwrap().clone()
+    }
+
+    /// Balance of a holding, zero if it was never created.
+    fn balance(&self, account_id: &AccountId) -> u128 {
+        let account = self.account(account_id);
+        if account == Account::default() {
+            return 0;
+        }
+        TokenHolding::decode(&account.data).unwrap().balance
+    }
+
+    /// Run `tx` with the current ledger page appended, and commit its effects
+    /// if it succeeds. Returns whether it did.
+    fn run(&mut self, mut tx: TreasuryTx) -> bool {
+        tx.account_ids.push(self.ledger_page_id());
+        let pre_accounts = tx.account_ids.iter().map(|id| self.account(id)).collect();
+        let Ok(simulated) = dry_run::simulate(&self.config, &tx, pre_accounts) else {
+            return false;
+        };
+        for change in simulated.changes {
+            self.accounts.insert(change.account_id, change.after);
+        }
+        true
+    }
+
+    fn tx(&self, op: &Op) -> TreasuryTx {
+        match *op {
+            Op::Send { user, amount } => TreasuryTx {
+                account_ids: vec![self.state_id(), self.vault_id(), holding_id(user), admin_id()],
+                instruction: Instruction::Send {
+                    amount,
+                    token_program_id: TOKEN_PROGRAM_ID,
+                    memo: None,
+                    private_recipient: false,
+                },
+                signers: vec![admin_id()],
+            },
+            Op::Deposit { user, amount } => TreasuryTx {
+                account_ids: vec![
+                    self.state_id(),
+                    holding_id(user),
+                    self.vault_id(),
+                    self.share_definition_id(),
+                    share_holding_id(user),
+                ],
+                instruction: Instruction::Deposit {
+                    amount,
+                    token_program_id: TOKEN_PROGRAM_ID,
+                    memo: None,
+                    private_sender: false,
+                },
+                signers: vec![holding_id(user), share_holding_id(user)],
+            },
+            Op::Redeem { user, shares } => TreasuryTx {
+                account_ids: vec![
+                    self.state_id(),
+                    self.vault_id(),
+                    self.share_definition_id(),
+                    share_holding_id(user),
+                    holding_id(user),
+                ],
+                instruction: Instruction::Redeem {
+                    shares,
+                    token_program_id: TOKEN_PROGRAM_ID,
+                },
+                signers: vec![share_holding_id(user)],
+            },
+            Op::Freeze | Op::Unfreeze => TreasuryTx {
+                account_ids: vec![self.state_id(), self.vault_id(), admin_id()],
+                instruction: if matches!(op, Op::Freeze) {
+                    Instruction::FreezeVault
+                } else {
+                    Instruction::UnfreezeVault
+                },
+                signers: vec![admin_id()],
+            },
+        }
+    }
+}
+
+fn definition_id() -> AccountId {
+    AccountId::new([7; 32])
+}
+
+fn admin_id() -> AccountId {
+    AccountId::new([9; 32])
+}
+
+fn holding_id(user: usize) -> AccountId {
+    AccountId::new([100 + user as u8; 32])
+}
+
+fn share_holding_id(user: usize) -> AccountId {
+    AccountId::new([110 + user as u8; 32])
+}
+
+/// Model of the vault: the totals the ledger and the vault registry must
+/// agree on, and the balances each op moves.
+#[derive(Debug, Default)]
+struct Expected {
+    ledger_len: u64,
+    sent: u128,
+    deposited: u128,
+    send_count: u64,
+    deposit_count: u64,
+    frozen: bool,
+    vault_balance: u128,
+    treasury_shares: u128,
+    share_supply: u128,
+    balances: [u128; USERS],
+    shares: [u128; USERS],
+}
+
+impl Expected {
+    /// The vault right after [`World::new`].
+    fn new() -> Self {
+        Self {
+            ledger_len: 2,
+            vault_balance: INITIAL_SUPPLY,
+            ..Self::default()
+        }
+    }
+
+    /// Whether `op` should succeed; if so, apply it to the model.
+    fn apply(&mut self, op: &Op) -> bool {
+        match *op {
+            Op::Send { user, amount } => {
+                if self.frozen || amount > self.vault_balance {
+                    return false;
+                }
+                self.vault_balance -= amount;
+                self.balances[user] += amount;
+                self.sent += amount;
+                self.send_count += 1;
+            }
+            Op::Deposit { user, amount } => {
+                if self.frozen || amount > self.balances[user] {
+                    return false;
+                }
+                // With no depositor shares out, the treasury's stake is re-based
+                // on the vault balance
+                let treasury_shares = if self.share_supply == 0 {
+                    self.vault_balance
+                } else {
+                    self.treasury_shares
+                };
+                let total_shares = self.share_supply + treasury_shares;
+                let shares = match shares_for_deposit(amount, self.vault_balance, total_shares) {
+                    Ok(shares) if shares > 0 => shares,
+                    _ => return false,
+                };
+                self.treasury_shares = treasury_shares;
+                self.share_supply += shares;
+                self.shares[user] += shares;
+                self.balances[user] -= amount;
+                self.vault_balance += amount;
+                self.deposited += amount;
+                self.deposit_count += 1;
+            }
+            Op::Redeem { user, shares } => {
+                if self.frozen || shares == 0 || shares > self.shares[user] {
+                    return false;
+                }
+                let total_shares = self.share_supply + self.treasury_shares;
+                let payout = match redemption_amount(shares, self.vault_balance, total_shares) {
+                    Ok(payout) if payout > 0 => payout,
+                    _ => return false,
+                };
+                self.share_supply -= shares;
+                self.shares[user] -= shares;
+                self.vault_balance -= payout;
+                self.balances[user] += payout;
+            }
+            Op::Freeze => self.frozen = true,
+            Op::Unfreeze => self.frozen = false,
+        }
+        self.ledger_len += 1;
+        true
+    }
+}
+
+fn check_invariants(world: &World, expected: &Expected) {
+    let state = world.state().unwrap();
+    let vault = world.vault();
+
+    // Counters match the registry and what succeeded
+    assert_eq!(state.vault_count, state.vaults.len() as u64);
+    assert_eq!(state.ledger_len, expected.ledger_len);
+    assert_eq!(vault.sent, expected.sent);
+    assert_eq!(vault.deposited, expected.deposited);
+    assert_eq!(vault.send_count, expected.send_count);
+    assert_eq!(vault.deposit_count, expected.deposit_count);
+    assert_eq!(vault.status == VaultStatus::Frozen, expected.frozen);
+    assert_eq!(vault.share_supply, expected.share_supply);
+
+    // Every balance moved as modelled
+    assert_eq!(world.balance(&world.vault_id()), expected.vault_balance);
+    for (user, balance) in expected.balances.iter().enumerate() {
+        assert_eq!(world.balance(&holding_id(user)), *balance);
+    }
+    for (user, shares) in expected.shares.iter().enumerate() {
+        assert_eq!(world.balance(&share_holding_id(user)), *shares);
+    }
+
+    // No tokens are created or lost, so no balance went negative
+    let vault_balance = world.balance(&world.vault_id());
+    let user_balances: u128 = (0..USERS).map(|user| world.balance(&holding_id(user))).sum();
+    assert_eq!(vault_balance + user_balances, INITIAL_SUPPLY);
+
+    // Outstanding shares are exactly the depositors' share balances
+    let shares: u128 = (0..USERS)
+        .map(|user| world.balance(&share_holding_id(user)))
+        .sum();
+    assert_eq!(vault.share_supply, shares);
+    let share_definition = world.account(&world.share_definition_id());
+    if share_definition != Account::default() {
+        let Ok(TokenDefinition::Fungible { total_supply, .. }) =
+            TokenDefinition::try_from(&share_definition.data)
+        else {
+            panic!("share definition is not a fungible token definition");
+        };
+        assert_eq!(total_supply, shares);
+    }
+
+    // Every ledger entry is on record, in sequence
+    let pages: Vec<Vec<u8>> = (0..=state.ledger_len / LEDGER_PAGE_ENTRIES)
+        .map(|page| {
+            let page_id = compute_ledger_page_pda(&TREASURY_PROGRAM_ID, &world.treasury(), page);
+            world.account(&page_id).data.to_vec()
+        })
+        .collect();
+    let entries: Vec<_> = ledger_entries(pages.iter().map(Vec::as_slice)).collect();
+    assert_eq!(entries.len() as u64, state.ledger_len);
+    assert!(entries.iter().enumerate().all(|(seq, entry)| entry.seq == seq as u64));
+    assert_eq!(entries[0].kind, LedgerKind::Initialize);
+    assert_eq!(entries[1].kind, LedgerKind::CreateVault);
+}
+
+proptest! {
+    #[test]
+    fn random_sequences_keep_the_treasury_consistent(ops in prop::collection::vec(op(), 1..60)) {
+        let mut world = World::new();
+        let mut expected = Expected::new();
+        check_invariants(&world, &expected);
+
+        for op in &ops {
+            let tx = world.tx(op);
+            let expected_ok = expected.apply(op);
+            prop_assert_eq!(world.run(tx), expected_ok, "{:?}", op);
+            check_invariants(&world, &expected);
+        }
+    }
+}
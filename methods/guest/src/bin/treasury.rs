@@ -14,11 +14,12 @@ fn main() {
     // Clone for process since it needs mutable references
     let mut accounts = program_input.pre_states.clone();
 
-    // Process the instruction
+    // Process the instruction; a rejected instruction aborts the execution
     let output = treasury_program::process(
         &mut accounts,
         &program_input.instruction,
-    );
+    )
+    .unwrap_or_else(|err| panic!("Treasury instruction rejected: {err}"));
 
     // Write outputs back to the zkVM
     write_nssa_outputs_with_chained_call(
@@ -1,9 +1,10 @@
 // treasury_core — shared types and PDA derivation helpers for the Treasury program.
 
 use borsh::{BorshDeserialize, BorshSerialize};
-use nssa_core::account::AccountId;
+use nssa_core::account::{AccountId, Data};
 use nssa_core::program::{PdaSeed, ProgramId};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 // ---------------------------------------------------------------------------
 // Instructions
@@ -13,35 +14,392 @@ use serde::{Deserialize, Serialize};
 /// 
 /// This treasury demonstrates PDA patterns with Token program integration.
 /// It creates token vaults and can send tokens from them.
+///
+/// One deployment hosts any number of treasuries, each identified by a
+/// [`TreasuryId`]. An instruction acts on the treasury whose state is its
+/// first account; the ID is recorded in the state when Initialize creates it.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Instruction {
     /// Create a new vault for a token.
     ///
     /// Chains to Token::NewFungibleDefinition to create a new token definition
-    /// and mint the initial supply into the treasury's PDA vault.
+    /// and mint the initial supply into the treasury's PDA vault. The admin
+    /// must sign, so the treasury must have been created by Initialize.
     CreateVault {
-        /// Name of the token (up to 6 bytes)
+        /// Name of the token, 1 to [`MAX_TOKEN_NAME_LEN`] bytes; longer names
+        /// are rejected, not truncated
         token_name: String,
+        /// Display name, ticker and decimals, recorded in the vault registry
+        metadata: TokenMetadata,
         /// Initial supply to mint
         initial_supply: u128,
         /// The token program ID to chain to
         token_program_id: ProgramId,
     },
 
-    /// Send tokens from the treasury vault to a recipient.
+    /// Send tokens from the treasury vault to a recipient. The admin must sign.
     Send {
         /// Amount to send
         amount: u128,
         /// The token program ID to chain to
         token_program_id: ProgramId,
+        /// Payment reference recorded in the ledger, up to [`MAX_MEMO_LEN`]
+        /// bytes
+        memo: Option<String>,
+        /// Record a zero counterparty in the ledger instead of the recipient,
+        /// so a private recipient's account ID stays private
+        private_recipient: bool,
     },
 
     /// Deposit tokens into the treasury vault from an external sender.
+    ///
+    /// Mints the depositor vault shares pro-rata to the vault balance and the
+    /// outstanding shares. The first deposit creates the share definition PDA.
     Deposit {
         /// Amount to deposit
         amount: u128,
         /// The token program ID to chain to
         token_program_id: ProgramId,
+        /// Payment reference recorded in the ledger, up to [`MAX_MEMO_LEN`]
+        /// bytes
+        memo: Option<String>,
+        /// Record a zero counterparty in the ledger instead of the sender, so
+        /// a private sender's account ID stays private
+        private_sender: bool,
+    },
+
+    /// Burn vault shares and pay out the proportional amount from the vault.
+    Redeem {
+        /// Shares to burn
+        shares: u128,
+        /// The token program ID to chain to
+        token_program_id: ProgramId,
+    },
+
+    /// Create the treasury state with the signer as admin.
+    ///
+    /// The treasury ID is [`TreasuryId::from_name`] of the signer and `name`,
+    /// so nobody else can create a treasury under that ID. Only runs on a
+    /// treasury that does not exist yet.
+    Initialize {
+        /// Name of the treasury, unique per creator
+        name: String,
+        /// This program's ID, which the state and first ledger page must be
+        /// derived from. A wrong ID strands the new state: every later
+        /// instruction rejects a state that is not at its PDA.
+        treasury_program_id: ProgramId,
+    },
+
+    /// Create a recurring payment from a vault (admin only).
+    ///
+    /// Pays `amount` to `recipient` every `interval` clock units, `count`
+    /// times, the first payment falling due at `start`.
+    CreateSchedule {
+        /// Token holding that receives the payments
+        recipient: AccountId,
+        /// Amount paid per installment
+        amount: u128,
+        /// Clock units between installments
+        interval: u64,
+        /// Time the first installment falls due
+        start: u64,
+        /// Number of installments
+        count: u64,
+    },
+
+    /// Pay every installment of a schedule that has fallen due.
+    ///
+    /// Permissionless, so a keeper can run it once a period has elapsed.
+    ExecuteDue {
+        /// Index of the schedule within its vault
+        schedule: u64,
+        /// The token program ID to chain to
+        token_program_id: ProgramId,
+    },
+
+    /// Move funds from a vault into a new escrow (admin only).
+    ///
+    /// The funds sit in the escrow's holding PDA until the arbiter releases
+    /// or refunds them, or the admin reclaims them after the deadline.
+    CreateEscrow {
+        /// Vault holding the funds are paid from; must be the `vault_holding`
+        /// account
+        payer_vault: AccountId,
+        /// Token holding paid on release
+        beneficiary: AccountId,
+        /// Amount held in escrow
+        amount: u128,
+        /// Account allowed to release or refund the funds
+        arbiter: AccountId,
+        /// Time after which the admin can reclaim the funds
+        deadline: u64,
+        /// The token program ID to chain to
+        token_program_id: ProgramId,
+    },
+
+    /// Pay an open escrow to its beneficiary (arbiter only).
+    Release {
+        /// Escrow ID
+        escrow: u64,
+        /// The token program ID to chain to
+        token_program_id: ProgramId,
+    },
+
+    /// Return an open escrow to its vault (arbiter only).
+    Refund {
+        /// Escrow ID
+        escrow: u64,
+        /// The token program ID to chain to
+        token_program_id: ProgramId,
+    },
+
+    /// Return an open escrow to its vault once its deadline has passed
+    /// (admin only).
+    Reclaim {
+        /// Escrow ID
+        escrow: u64,
+        /// The token program ID to chain to
+        token_program_id: ProgramId,
+    },
+
+    /// Give an account a role (admin only).
+    AssignRole {
+        role: Role,
+        account: AccountId,
+    },
+
+    /// Take a role away from an account (admin only).
+    RevokeRole {
+        role: Role,
+        account: AccountId,
+    },
+
+    /// Fund a milestone grant from a vault (admin only).
+    ///
+    /// The paying vault is the `vault_holding` account. The total of all
+    /// milestones moves into the grant's holding PDA up front.
+    CreateGrant {
+        /// Token holding paid as milestones are approved
+        grantee: AccountId,
+        /// Tranche amounts, in the order they are released
+        milestones: Vec<u128>,
+        /// The token program ID to chain to
+        token_program_id: ProgramId,
+    },
+
+    /// Release the next milestone of a grant to the grantee (reviewer only).
+    ApproveMilestone {
+        /// Grant ID
+        grant: u64,
+        /// Index of the milestone; must be the next pending one
+        milestone: u32,
+        /// The token program ID to chain to
+        token_program_id: ProgramId,
+    },
+
+    /// Cancel the remaining milestones of a grant and return their funds to
+    /// the vault (admin only).
+    CancelGrant {
+        /// Grant ID
+        grant: u64,
+        /// The token program ID to chain to
+        token_program_id: ProgramId,
+    },
+
+    /// Mint more of a vault's token into the vault (admin only).
+    ///
+    /// Only works for vaults whose token definition is a treasury PDA (see
+    /// `compute_token_definition_pda`), so the treasury can authorize the
+    /// mint. Respects the vault's supply cap, if any.
+    MintMore {
+        /// Amount to mint
+        amount: u128,
+        /// The token program ID to chain to
+        token_program_id: ProgramId,
+    },
+
+    /// Set or clear a vault's supply cap (admin only).
+    SetSupplyCap {
+        /// Most total supply MintMore may reach; `None` removes the cap
+        cap: Option<u128>,
+    },
+
+    /// Burn tokens held by a vault (admin only).
+    Burn {
+        /// Amount to burn
+        amount: u128,
+        /// The token program ID to chain to
+        token_program_id: ProgramId,
+    },
+
+    /// Set the AMM program Swap, AddLiquidity and RemoveLiquidity chain to
+    /// (admin only).
+    ///
+    /// The vaults authorize those calls with their PDA seeds, so the program
+    /// is pinned in the state rather than named by each instruction.
+    SetAmmProgram {
+        /// The AMM program ID to chain to
+        amm_program_id: ProgramId,
+    },
+
+    /// Swap between two treasury vaults through an AMM pool (admin only).
+    ///
+    /// The source vault is the `from_vault` account and pays `amount_in`;
+    /// the `to_vault` account receives the output.
+    Swap {
+        /// Amount paid from the source vault
+        amount_in: u128,
+        /// Least output accepted; the swap fails below this. Must be non-zero.
+        min_amount_out: u128,
+    },
+
+    /// Provide liquidity to an AMM pool from two treasury vaults (admin only).
+    ///
+    /// The vaults must hold the pool's A and B tokens. LP tokens are received
+    /// into the LP vault, the vault PDA of the pool's LP token definition,
+    /// which is registered on first use.
+    AddLiquidity {
+        /// Most paid from the A vault
+        max_amount_a: u128,
+        /// Most paid from the B vault
+        max_amount_b: u128,
+        /// Least LP tokens accepted; the deposit fails below this. Must be non-zero.
+        min_amount_lp: u128,
+    },
+
+    /// Withdraw liquidity from an AMM pool back into two treasury vaults
+    /// (admin only), paying LP tokens from the LP vault.
+    RemoveLiquidity {
+        /// LP tokens paid from the LP vault
+        lp_amount: u128,
+        /// Least received into the A vault. Must be non-zero.
+        min_amount_a: u128,
+        /// Least received into the B vault. Must be non-zero.
+        min_amount_b: u128,
+    },
+
+    /// Set or clear how DepositSplit divides a token's deposits (admin only).
+    ///
+    /// The token is the one held by the `vault_holding` account.
+    SetSplit {
+        /// The new split; `None` removes it
+        split: Option<SplitConfig>,
+    },
+
+    /// Deposit tokens from an external sender straight into the labeled
+    /// vaults of the token's split, one Token transfer per target.
+    DepositSplit {
+        /// Amount to deposit
+        amount: u128,
+        /// The token program ID to chain to
+        token_program_id: ProgramId,
+    },
+
+    /// Request a payment into a vault (admin only).
+    ///
+    /// The receiving vault is the `vault_holding` account. The invoice is
+    /// stored in a PDA of the next invoice ID until it is paid.
+    CreateInvoice {
+        /// Amount due
+        amount: u128,
+        /// Token holding that must pay, or `None` to accept any payer
+        payer: Option<AccountId>,
+        /// Time by which payment is due; PayInvoice is refused after it
+        due: u64,
+        /// Reference to match the payment against, up to [`MAX_MEMO_LEN`]
+        /// bytes; recorded in the ledger
+        reference: String,
+    },
+
+    /// Pay an open invoice in full, by its due time, from the payer's token
+    /// holding, which must sign.
+    PayInvoice {
+        /// Invoice ID
+        invoice: u64,
+        /// Amount paid; must equal the amount due
+        amount: u128,
+        /// The token program ID to chain to
+        token_program_id: ProgramId,
+    },
+
+    /// Move tokens between two vaults of the same token (admin only).
+    ///
+    /// The vaults are the `from_vault` and `to_vault` accounts: the token's
+    /// main vault or one of its labeled vaults, named here by label because
+    /// the label derives the vault's PDA seed. Both must already hold the
+    /// token.
+    InternalTransfer {
+        /// Label of the paying vault, or `None` for the main vault
+        from_vault: Option<String>,
+        /// Label of the receiving vault, or `None` for the main vault
+        to_vault: Option<String>,
+        /// Amount moved
+        amount: u128,
+        /// The token program ID to chain to
+        token_program_id: ProgramId,
+    },
+
+    /// Pay from a vault into the vault of another treasury deployment
+    /// (admin only).
+    ///
+    /// Chains to the target treasury's Deposit, so the target records the
+    /// payment like any deposit. The shares it mints are received into this
+    /// treasury's vault PDA of the target's share definition.
+    FundTreasury {
+        /// Program ID of the receiving treasury
+        target_treasury_program_id: ProgramId,
+        /// ID of the receiving treasury within its deployment
+        target_treasury: TreasuryId,
+        /// Token paid; the paying vault and the receiving vault both hold it
+        token_definition: AccountId,
+        /// Amount paid
+        amount: u128,
+        /// The token program ID both treasuries chain to
+        token_program_id: ProgramId,
+    },
+
+    /// Redeem shares that FundTreasury received from another treasury
+    /// (admin only).
+    ///
+    /// Chains to the target treasury's Redeem, authorizing this treasury's
+    /// vault PDA of the target's share definition as the share holding. The
+    /// payout goes back into this treasury's vault of the token.
+    RedeemFromTreasury {
+        /// Program ID of the treasury that minted the shares
+        target_treasury_program_id: ProgramId,
+        /// ID of that treasury within its deployment
+        target_treasury: TreasuryId,
+        /// Token the shares are a claim on; held by both vaults
+        token_definition: AccountId,
+        /// Shares to redeem
+        shares: u128,
+        /// The token program ID both treasuries chain to
+        token_program_id: ProgramId,
+    },
+    /// Stop all funds moving into or out of a vault (admin or guardian).
+    ///
+    /// The vault is the `vault_holding` account. Its labeled vaults are
+    /// frozen with it.
+    FreezeVault,
+
+    /// Let funds move into and out of a frozen vault again (admin or
+    /// guardian).
+    UnfreezeVault,
+
+    /// Set the treasury's name, description URI and tags (admin only),
+    /// replacing any earlier metadata.
+    ///
+    /// Stored in the metadata PDA so explorers and wallets can show who the
+    /// treasury belongs to. See [`TreasuryMetadata::validate`] for the
+    /// bounds.
+    SetMetadata {
+        /// Display name
+        name: String,
+        /// Link to a longer description, such as a website or a JSON
+        /// document; may be empty
+        uri: String,
+        /// Short keywords, such as a contact handle or a category
+        tags: Vec<String>,
     },
 }
 
@@ -49,48 +407,1448 @@ pub enum Instruction {
 // Vault state (persisted in the treasury_state PDA)
 // ---------------------------------------------------------------------------
 
+/// Longest token display name, in bytes.
+pub const MAX_DISPLAY_NAME_LEN: usize = 64;
+
+/// Longest ticker, in bytes.
+pub const MAX_TICKER_LEN: usize = 12;
+
+/// Most decimals a token can have. `10^MAX_DECIMALS` fits comfortably in a
+/// u128.
+pub const MAX_DECIMALS: u8 = 18;
+
+/// How clients should render a vault's token. The token definition only
+/// carries a short name, so the treasury records the rest.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct TokenMetadata {
+    /// Full display name.
+    pub display_name: String,
+    /// Short symbol shown next to amounts.
+    pub ticker: String,
+    /// Digits after the decimal point: an amount of `n` raw units is
+    /// `n / 10^decimals` tokens.
+    pub decimals: u8,
+}
+
+impl TokenMetadata {
+    /// Check the bounds: a display name of 1 to [`MAX_DISPLAY_NAME_LEN`]
+    /// bytes, a ticker of 1 to [`MAX_TICKER_LEN`] bytes and at most
+    /// [`MAX_DECIMALS`] decimals.
+    pub fn validate(&self) -> Result<(), TreasuryError> {
+        if self.display_name.is_empty()
+            || self.display_name.len() > MAX_DISPLAY_NAME_LEN
+            || self.ticker.is_empty()
+            || self.ticker.len() > MAX_TICKER_LEN
+            || self.decimals > MAX_DECIMALS
+        {
+            return Err(TreasuryError::InvalidTokenMetadata);
+        }
+        Ok(())
+    }
+
+    /// Render a raw amount in whole tokens, e.g. `1234567` with 6 decimals
+    /// as `1.234567`. Trailing zeros after the point are dropped.
+    pub fn format_amount(&self, amount: u128) -> String {
+        let Some(scale) = 10u128.checked_pow(u32::from(self.decimals)) else {
+            return amount.to_string();
+        };
+        let whole = amount / scale;
+        let fraction = amount % scale;
+        if fraction == 0 {
+            return whole.to_string();
+        }
+        let digits = format!("{fraction:0width$}", width = usize::from(self.decimals));
+        format!("{whole}.{}", digits.trim_end_matches('0'))
+    }
+}
+
+/// A vault registered in the treasury state.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct VaultRecord {
+    /// Token definition held by the vault.
+    pub token_definition_id: AccountId,
+    /// The vault holding PDA.
+    pub holding_id: AccountId,
+    /// Share tokens minted to depositors and not yet redeemed.
+    pub share_supply: u128,
+    /// The treasury's own stake in the vault, in shares. Set to the vault
+    /// balance whenever a deposit arrives with no depositor shares
+    /// outstanding, so depositors only ever own the share of the vault they
+    /// paid for. Never minted as tokens.
+    pub treasury_shares: u128,
+    /// How many payment schedules have been created for the vault.
+    pub schedule_count: u64,
+    /// Most total supply MintMore may reach, if capped.
+    pub supply_cap: Option<u128>,
+    /// Total minted into the vault by MintMore.
+    pub minted: u128,
+    /// Total burned from the vault by Burn.
+    pub burned: u128,
+    /// Total received by Deposit over the vault's lifetime. Other inflows,
+    /// such as PayInvoice or a refunded escrow, are only in the ledger.
+    pub deposited: u128,
+    /// Total paid out by Send over the vault's lifetime. Other outflows,
+    /// such as ExecuteDue or Burn, are only in the ledger.
+    pub sent: u128,
+    /// Number of Deposit instructions into the vault.
+    pub deposit_count: u64,
+    /// Number of Send instructions from the vault.
+    pub send_count: u64,
+    /// How DepositSplit divides deposits of the token, if set.
+    pub split: Option<SplitConfig>,
+    /// Whether funds may move into and out of the vault.
+    pub status: VaultStatus,
+    /// How to render the token, if recorded. CreateVault records it; vaults
+    /// registered otherwise, such as LP vaults, have none.
+    pub metadata: Option<TokenMetadata>,
+}
+
+impl VaultRecord {
+    /// A freshly created vault with no shares outstanding.
+    pub fn new(token_definition_id: AccountId, holding_id: AccountId) -> Self {
+        Self {
+            token_definition_id,
+            holding_id,
+            share_supply: 0,
+            treasury_shares: 0,
+            schedule_count: 0,
+            supply_cap: None,
+            minted: 0,
+            burned: 0,
+            deposited: 0,
+            sent: 0,
+            deposit_count: 0,
+            send_count: 0,
+            split: None,
+            status: VaultStatus::Active,
+            metadata: None,
+        }
+    }
+
+    /// All outstanding shares, minted or not.
+    pub fn total_shares(&self) -> Option<u128> {
+        self.share_supply.checked_add(self.treasury_shares)
+    }
+
+    /// Fail with [`TreasuryError::VaultFrozen`] if the vault is frozen.
+    pub fn require_active(&self) -> Result<(), TreasuryError> {
+        match self.status {
+            VaultStatus::Active => Ok(()),
+            VaultStatus::Frozen => Err(TreasuryError::VaultFrozen),
+        }
+    }
+}
+
+/// Whether funds may move through a vault.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub enum VaultStatus {
+    /// Funds move freely.
+    Active,
+    /// Every instruction moving funds into or out of the vault is rejected.
+    Frozen,
+}
+
+/// Shares minted for depositing `amount` into a vault holding `vault_balance`
+/// (before the deposit) with `total_shares` outstanding. Rounds down, in
+/// favour of existing holders.
+///
+/// Fails if shares are outstanding against an empty vault. Deposit avoids
+/// this while no depositor holds shares by re-basing the treasury's stake;
+/// otherwise the vault must be topped up before it takes deposits again.
+pub fn shares_for_deposit(
+    amount: u128,
+    vault_balance: u128,
+    total_shares: u128,
+) -> Result<u128, TreasuryError> {
+    if total_shares == 0 {
+        return Ok(amount);
+    }
+    if vault_balance == 0 {
+        // Outstanding shares with nothing backing them: a deposit would
+        // only fund the existing holders.
+        return Err(TreasuryError::InvalidShareAmount);
+    }
+    amount
+        .checked_mul(total_shares)
+        .and_then(|value| value.checked_div(vault_balance))
+        .ok_or(TreasuryError::Overflow)
+}
+
+/// Tokens paid out for redeeming `shares` from a vault holding
+/// `vault_balance` with `total_shares` outstanding. Rounds down, in favour
+/// of the remaining holders.
+pub fn redemption_amount(
+    shares: u128,
+    vault_balance: u128,
+    total_shares: u128,
+) -> Result<u128, TreasuryError> {
+    if shares > total_shares {
+        return Err(TreasuryError::InvalidShareAmount);
+    }
+    shares
+        .checked_mul(vault_balance)
+        .and_then(|value| value.checked_div(total_shares))
+        .ok_or(TreasuryError::Overflow)
+}
+
 /// State stored in the treasury_state PDA.
 #[derive(Debug, Clone, Default, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 pub struct TreasuryState {
     /// How many vaults have been created.
     pub vault_count: u64,
+    /// Registry of vaults, in creation order.
+    pub vaults: Vec<VaultRecord>,
+    /// Account allowed to run admin instructions: the creator, set by
+    /// `Initialize`.
+    pub admin: Option<AccountId>,
+    /// How many escrows have been created. Escrow IDs count up from zero.
+    pub escrow_count: u64,
+    /// Roles assigned by the admin.
+    pub roles: Vec<RoleAssignment>,
+    /// How many grants have been created. Grant IDs count up from zero.
+    pub grant_count: u64,
+    /// How many entries have been appended to the ledger. The next entry
+    /// gets this sequence number.
+    pub ledger_len: u64,
+    /// How many invoices have been created. Invoice IDs count up from zero.
+    pub invoice_count: u64,
+    /// The treasury this state belongs to. Every PDA of the treasury is
+    /// derived from it.
+    pub treasury_id: TreasuryId,
+    /// AMM program swaps and liquidity chain to, set by `SetAmmProgram`.
+    pub amm_program_id: Option<ProgramId>,
+}
+
+impl TreasuryState {
+    /// Look up the vault registered for a token definition.
+    pub fn vault(&self, token_definition_id: &AccountId) -> Option<&VaultRecord> {
+        self.vaults
+            .iter()
+            .find(|vault| vault.token_definition_id == *token_definition_id)
+    }
+
+    /// Mutable lookup of the vault registered for a token definition.
+    pub fn vault_mut(&mut self, token_definition_id: &AccountId) -> Option<&mut VaultRecord> {
+        self.vaults
+            .iter_mut()
+            .find(|vault| vault.token_definition_id == *token_definition_id)
+    }
+
+    /// Registry position of the vault for a token definition.
+    pub fn vault_index(&self, token_definition_id: &AccountId) -> Option<usize> {
+        self.vaults
+            .iter()
+            .position(|vault| vault.token_definition_id == *token_definition_id)
+    }
+
+    /// Whether `account` has been assigned `role`.
+    pub fn has_role(&self, role: Role, account: &AccountId) -> bool {
+        self.roles
+            .iter()
+            .any(|assignment| assignment.role == role && assignment.account == *account)
+    }
+}
+
+/// Roles the admin can assign in addition to its own.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub enum Role {
+    /// Approves grant milestones.
+    Reviewer,
+    /// Freezes and unfreezes vaults.
+    Guardian,
+}
+
+/// An account holding a role.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct RoleAssignment {
+    pub role: Role,
+    pub account: AccountId,
 }
 
 // ---------------------------------------------------------------------------
-// PDA derivation helpers
+// Payment schedules (persisted in per-vault schedule PDAs)
 // ---------------------------------------------------------------------------
 
-/// Fixed 32-byte seed for treasury state PDA.
-const TREASURY_STATE_SEED: [u8; 32] = {
-    let mut seed = [0u8; 32];
-    let tag = b"treasury_state";
+/// A recurring payment from a vault.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct Schedule {
+    /// Token definition of the paying vault.
+    pub token_definition_id: AccountId,
+    /// Token holding that receives the payments.
+    pub recipient: AccountId,
+    /// Amount paid per installment.
+    pub amount: u128,
+    /// Clock units between installments.
+    pub interval: u64,
+    /// Time the first installment falls due.
+    pub start: u64,
+    /// Number of installments.
+    pub count: u64,
+    /// Installments paid so far.
+    pub paid: u64,
+}
+
+impl Schedule {
+    /// Installments that have fallen due by `now`, paid or not.
+    pub fn due_count(&self, now: u64) -> u64 {
+        let Some(since_start) = now.checked_sub(self.start) else {
+            return 0;
+        };
+        let elapsed = since_start.checked_div(self.interval).unwrap_or(u64::MAX);
+        elapsed.saturating_add(1).min(self.count)
+    }
+
+    /// Time the next unpaid installment falls due, or `None` once all
+    /// installments are paid.
+    pub fn next_due(&self) -> Option<u64> {
+        if self.paid >= self.count {
+            return None;
+        }
+        self.paid
+            .checked_mul(self.interval)
+            .and_then(|offset| self.start.checked_add(offset))
+    }
+}
+
+/// Account ID of the chain's clock account, padded with zeros.
+const CLOCK_ACCOUNT_ID: [u8; 32] = {
+    let mut id = [0u8; 32];
+    let tag = b"/LEZ/ClockAccount";
     let mut i = 0;
     while i < tag.len() {
-        seed[i] = tag[i];
+        id[i] = tag[i];
         i += 1;
     }
-    seed
+    id
 };
 
+/// The chain's clock account, whose data the sequencer keeps at the current
+/// time. Instructions that read the time only accept this account, so no
+/// caller can bring a clock of their own.
+pub fn clock_account_id() -> AccountId {
+    AccountId::new(CLOCK_ACCOUNT_ID)
+}
+
+/// Read the current time from a clock account's data: a u64 LE timestamp
+/// in its first 8 bytes.
+pub fn read_clock(data: &[u8]) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(..8)?.try_into().ok()?))
+}
+
+// ---------------------------------------------------------------------------
+// Split payments (persisted in the vault registry)
+// ---------------------------------------------------------------------------
+
+/// Basis points in a whole: split targets' shares add up to this.
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Most targets a split can have.
+pub const MAX_SPLIT_TARGETS: usize = 8;
+
+/// Longest vault label, in bytes.
+pub const MAX_LABEL_LEN: usize = 32;
+
+/// One labeled vault receiving part of a split deposit.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct SplitTarget {
+    /// Label of the receiving vault; see [`compute_labeled_vault_pda`].
+    pub label: String,
+    /// Share of each deposit, in basis points.
+    pub bps: u16,
+}
+
+/// How deposits of a token are divided between labeled vaults.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct SplitConfig {
+    /// Receiving vaults, in the order their transfers are made.
+    pub targets: Vec<SplitTarget>,
+    /// Label of the target that also receives the rounding dust.
+    pub dust_label: String,
+}
+
+impl SplitConfig {
+    /// Check the split: 1 to [`MAX_SPLIT_TARGETS`] targets with distinct
+    /// labels of 1 to [`MAX_LABEL_LEN`] bytes and non-zero shares adding up
+    /// to [`BPS_DENOMINATOR`], one of them the dust target.
+    pub fn validate(&self) -> Result<(), TreasuryError> {
+        let targets = &self.targets;
+        let mut total: u32 = 0;
+        for (i, target) in targets.iter().enumerate() {
+            let label_ok = !target.label.is_empty() && target.label.len() <= MAX_LABEL_LEN;
+            let unique = targets[..i].iter().all(|other| other.label != target.label);
+            if !label_ok || !unique || target.bps == 0 {
+                return Err(TreasuryError::InvalidSplit);
+            }
+            total = total
+                .checked_add(u32::from(target.bps))
+                .ok_or(TreasuryError::InvalidSplit)?;
+        }
+        let has_dust_target = targets.iter().any(|target| target.label == self.dust_label);
+        if targets.is_empty()
+            || targets.len() > MAX_SPLIT_TARGETS
+            || total != u32::from(BPS_DENOMINATOR)
+            || !has_dust_target
+        {
+            return Err(TreasuryError::InvalidSplit);
+        }
+        Ok(())
+    }
+
+    /// Divide `amount` between the targets, in target order. Each target
+    /// gets its share rounded down; the remainder goes to the dust target,
+    /// so the parts always add up to `amount`. The split must pass
+    /// [`SplitConfig::validate`]; otherwise the shares may add up to more
+    /// than `amount`, which fails with [`TreasuryError::InvalidSplit`].
+    pub fn amounts(&self, amount: u128) -> Result<Vec<u128>, TreasuryError> {
+        let denominator = u128::from(BPS_DENOMINATOR);
+        // amount × bps / denominator, without overflowing for large amounts
+        let share = |bps: u16| {
+            let bps = u128::from(bps);
+            let whole = (amount / denominator).checked_mul(bps)?;
+            let rest = (amount % denominator).checked_mul(bps)? / denominator;
+            whole.checked_add(rest)
+        };
+        let mut amounts = self
+            .targets
+            .iter()
+            .map(|target| share(target.bps).ok_or(TreasuryError::Overflow))
+            .collect::<Result<Vec<u128>, _>>()?;
+        let paid = amounts
+            .iter()
+            .try_fold(0u128, |paid, part| paid.checked_add(*part))
+            .ok_or(TreasuryError::Overflow)?;
+        let dust = amount.checked_sub(paid).ok_or(TreasuryError::InvalidSplit)?;
+        let dust_target = self
+            .targets
+            .iter()
+            .position(|target| target.label == self.dust_label);
+        if let Some(position) = dust_target {
+            amounts[position] = amounts[position]
+                .checked_add(dust)
+                .ok_or(TreasuryError::Overflow)?;
+        }
+        Ok(amounts)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Escrows (persisted in per-escrow PDAs)
+// ---------------------------------------------------------------------------
+
+/// Lifecycle of an escrow.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub enum EscrowStatus {
+    /// Funds are held in the escrow holding.
+    Open,
+    /// Paid to the beneficiary by the arbiter.
+    Released,
+    /// Returned to the vault by the arbiter.
+    Refunded,
+    /// Returned to the vault by the admin after the deadline.
+    Reclaimed,
+}
+
+/// Funds held for a beneficiary until an arbiter decides.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct Escrow {
+    /// Token definition of the escrowed funds.
+    pub token_definition_id: AccountId,
+    /// Vault the funds came from and are refunded to.
+    pub vault_id: AccountId,
+    /// Token holding paid on release.
+    pub beneficiary: AccountId,
+    /// Account allowed to release or refund the funds.
+    pub arbiter: AccountId,
+    /// Amount held in escrow.
+    pub amount: u128,
+    /// Time after which the admin can reclaim the funds.
+    pub deadline: u64,
+    pub status: EscrowStatus,
+}
+
+// ---------------------------------------------------------------------------
+// Invoices (persisted in per-invoice PDAs)
+// ---------------------------------------------------------------------------
+
+/// Lifecycle of an invoice.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub enum InvoiceStatus {
+    /// Awaiting payment.
+    Open,
+    /// Paid in full.
+    Paid,
+}
+
+/// A payment requested into a vault.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct Invoice {
+    /// Token definition the invoice is paid in.
+    pub token_definition_id: AccountId,
+    /// Vault the payment goes to.
+    pub vault_id: AccountId,
+    /// Amount due.
+    pub amount: u128,
+    /// Token holding that must pay, or `None` if anyone may.
+    pub payer: Option<AccountId>,
+    /// Time by which payment is due. Later payments are refused.
+    pub due: u64,
+    /// Reference to match the payment against.
+    pub reference: String,
+    pub status: InvoiceStatus,
+    /// Token holding that paid the invoice, once paid.
+    pub paid_by: Option<AccountId>,
+}
+
+// ---------------------------------------------------------------------------
+// Metadata (persisted in the metadata PDA)
+// ---------------------------------------------------------------------------
+
+/// Longest treasury name, in bytes.
+pub const MAX_METADATA_NAME_LEN: usize = 64;
+
+/// Longest description URI, in bytes.
+pub const MAX_METADATA_URI_LEN: usize = 200;
+
+/// Most tags a treasury can have.
+pub const MAX_METADATA_TAGS: usize = 8;
+
+/// Longest tag, in bytes.
+pub const MAX_METADATA_TAG_LEN: usize = 32;
+
+/// Who the treasury belongs to, for explorers and wallets to display.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct TreasuryMetadata {
+    /// Display name.
+    pub name: String,
+    /// Link to a longer description; empty if there is none.
+    pub uri: String,
+    /// Short keywords, such as a contact handle or a category.
+    pub tags: Vec<String>,
+}
+
+impl TreasuryMetadata {
+    /// Check the bounds: a name of 1 to [`MAX_METADATA_NAME_LEN`] bytes, a
+    /// URI of at most [`MAX_METADATA_URI_LEN`] bytes, and at most
+    /// [`MAX_METADATA_TAGS`] distinct tags of 1 to [`MAX_METADATA_TAG_LEN`]
+    /// bytes.
+    pub fn validate(&self) -> Result<(), TreasuryError> {
+        let tags = &self.tags;
+        let tags_ok = tags.len() <= MAX_METADATA_TAGS
+            && tags.iter().enumerate().all(|(i, tag)| {
+                !tag.is_empty() && tag.len() <= MAX_METADATA_TAG_LEN && !tags[..i].contains(tag)
+            });
+        if self.name.is_empty()
+            || self.name.len() > MAX_METADATA_NAME_LEN
+            || self.uri.len() > MAX_METADATA_URI_LEN
+            || !tags_ok
+        {
+            return Err(TreasuryError::InvalidMetadata);
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Grants (persisted in per-grant PDAs)
+// ---------------------------------------------------------------------------
+
+/// Most milestones a single grant can have.
+pub const MAX_MILESTONES: usize = 32;
+
+/// Where a grant milestone stands.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub enum MilestoneState {
+    /// Waiting for review; funds are in the grant holding.
+    Pending,
+    /// Approved and paid to the grantee.
+    Released,
+    /// Cancelled; funds returned to the vault.
+    Cancelled,
+}
+
+/// One tranche of a grant.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub struct Milestone {
+    pub amount: u128,
+    pub state: MilestoneState,
+}
+
+/// A grant paid out in ordered milestones.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct Grant {
+    /// Token definition of the granted funds.
+    pub token_definition_id: AccountId,
+    /// Vault the grant was funded from; cancelled funds return here.
+    pub vault_id: AccountId,
+    /// Token holding paid as milestones are approved.
+    pub grantee: AccountId,
+    /// Tranches, in release order.
+    pub milestones: Vec<Milestone>,
+}
+
+impl Grant {
+    /// Decode a grant PDA's data. Returns `None` if it is not a grant.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        Self::try_from_slice(data).ok()
+    }
+
+    /// Index of the next milestone awaiting approval.
+    pub fn next_pending(&self) -> Option<usize> {
+        self.milestones
+            .iter()
+            .position(|milestone| milestone.state == MilestoneState::Pending)
+    }
+
+    /// Sum of the milestones in `state`.
+    pub fn total_in(&self, state: MilestoneState) -> Option<u128> {
+        self.milestones
+            .iter()
+            .filter(|milestone| milestone.state == state)
+            .try_fold(0u128, |total, milestone| total.checked_add(milestone.amount))
+    }
+
+    /// Summary of how far the grant has been paid out.
+    pub fn progress(&self) -> Option<GrantProgress> {
+        let count = |state| {
+            self.milestones
+                .iter()
+                .filter(|milestone| milestone.state == state)
+                .count()
+        };
+        Some(GrantProgress {
+            milestones: self.milestones.len(),
+            released_milestones: count(MilestoneState::Released),
+            cancelled_milestones: count(MilestoneState::Cancelled),
+            released: self.total_in(MilestoneState::Released)?,
+            pending: self.total_in(MilestoneState::Pending)?,
+            cancelled: self.total_in(MilestoneState::Cancelled)?,
+            next_milestone: self.next_pending(),
+        })
+    }
+}
+
+/// How far a grant has been paid out, for display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GrantProgress {
+    pub milestones: usize,
+    pub released_milestones: usize,
+    pub cancelled_milestones: usize,
+    /// Amount paid to the grantee.
+    pub released: u128,
+    /// Amount still held for pending milestones.
+    pub pending: u128,
+    /// Amount returned to the vault.
+    pub cancelled: u128,
+    /// Index of the next milestone awaiting approval.
+    pub next_milestone: Option<usize>,
+}
+
+// ---------------------------------------------------------------------------
+// Ledger (persisted in ledger page PDAs)
+// ---------------------------------------------------------------------------
+
+/// Entries per ledger page. Entry `seq` is on page `seq / LEDGER_PAGE_ENTRIES`.
+pub const LEDGER_PAGE_ENTRIES: u64 = 32;
+
+/// Size of an encoded ledger entry: kind (1), vault (32), counterparty (32),
+/// amount (16), seq (8), memo length (1), then the memo zero-padded to
+/// [`MAX_MEMO_LEN`].
+pub const LEDGER_ENTRY_SIZE: usize = MEMO_OFFSET + 1 + MAX_MEMO_LEN;
+
+/// Where the memo length byte sits in an encoded ledger entry.
+const MEMO_OFFSET: usize = 89;
+
+/// Longest memo Send and Deposit accept, in bytes.
+pub const MAX_MEMO_LEN: usize = 64;
+
+/// Check a Send or Deposit memo: if present, 1 to [`MAX_MEMO_LEN`] bytes.
+pub fn validate_memo(memo: Option<&str>) -> Result<(), TreasuryError> {
+    match memo {
+        Some(memo) if memo.is_empty() || memo.len() > MAX_MEMO_LEN => {
+            Err(TreasuryError::InvalidMemo)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The instruction that appended a ledger entry.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+pub enum LedgerKind {
+    CreateVault,
+    Send,
+    Deposit,
+    Redeem,
+    Initialize,
+    CreateSchedule,
+    ExecuteDue,
+    CreateEscrow,
+    Release,
+    Refund,
+    Reclaim,
+    AssignRole,
+    RevokeRole,
+    CreateGrant,
+    ApproveMilestone,
+    CancelGrant,
+    MintMore,
+    SetSupplyCap,
+    Burn,
+    SetAmmProgram,
+    Swap,
+    AddLiquidity,
+    RemoveLiquidity,
+    CreateInvoice,
+    PayInvoice,
+    SetSplit,
+    DepositSplit,
+    InternalTransfer,
+    FundTreasury,
+    RedeemFromTreasury,
+    FreezeVault,
+    UnfreezeVault,
+    SetMetadata,
+}
+
+/// One ledger entry. Ledger pages hold entries back to back, in sequence
+/// order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub kind: LedgerKind,
+    /// Holding of the vault involved, or the zero ID if none is.
+    pub vault: AccountId,
+    /// The other side: recipient, sender, signer or account acted on, or the
+    /// zero ID if there is none.
+    pub counterparty: AccountId,
+    /// Amount moved, minted or burned. For SetSupplyCap the new cap, for
+    /// AddLiquidity the least LP tokens accepted; zero when there is none.
+    pub amount: u128,
+    /// Position in the ledger, counting up from zero.
+    pub seq: u64,
+    /// Payment reference given to Send or Deposit.
+    pub memo: Option<String>,
+}
+
+impl LedgerEntry {
+    /// Encode as a fixed-size [`LEDGER_ENTRY_SIZE`]-byte record. The memo must
+    /// pass [`validate_memo`].
+    pub fn encode(&self) -> Vec<u8> {
+        let head = (self.kind, self.vault, self.counterparty, self.amount, self.seq);
+        let mut data = borsh::to_vec(&head).expect("ledger entries always encode");
+        let memo = self.memo.as_deref().unwrap_or_default().as_bytes();
+        data.push(memo.len() as u8);
+        data.extend_from_slice(memo);
+        data.resize(LEDGER_ENTRY_SIZE, 0);
+        data
+    }
+
+    /// Decode a single [`LEDGER_ENTRY_SIZE`]-byte record.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() != LEDGER_ENTRY_SIZE {
+            return None;
+        }
+        let (kind, vault, counterparty, amount, seq) =
+            BorshDeserialize::try_from_slice(&data[..MEMO_OFFSET]).ok()?;
+        let memo = match usize::from(data[MEMO_OFFSET]) {
+            0 => None,
+            len => {
+                let bytes = data.get(MEMO_OFFSET + 1..MEMO_OFFSET + 1 + len)?;
+                Some(String::from_utf8(bytes.to_vec()).ok()?)
+            }
+        };
+        Some(Self {
+            kind,
+            vault,
+            counterparty,
+            amount,
+            seq,
+            memo,
+        })
+    }
+}
+
+/// Number of entries stored on a ledger page, or `None` if the data is not a
+/// whole number of entries.
+pub fn ledger_page_len(data: &[u8]) -> Option<u64> {
+    data.len().is_multiple_of(LEDGER_ENTRY_SIZE).then_some((data.len() / LEDGER_ENTRY_SIZE) as u64)
+}
+
+/// Read ledger entries back in order from the data of pages 0, 1, 2, ...
+///
+/// Stops at the first record that doesn't decode.
+pub fn ledger_entries<'a>(
+    pages: impl IntoIterator<Item = &'a [u8]> + 'a,
+) -> impl Iterator<Item = LedgerEntry> + 'a {
+    pages
+        .into_iter()
+        .flat_map(|page| page.chunks(LEDGER_ENTRY_SIZE))
+        .map_while(LedgerEntry::decode)
+}
+
+// ---------------------------------------------------------------------------
+// Token program account layouts
+// ---------------------------------------------------------------------------
+
+/// A fungible Token program holding. Decoded from and encoded to account
+/// data with the Token program's own `token_core::TokenHolding`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenHolding {
+    /// Token definition this holding belongs to.
+    pub definition_id: AccountId,
+    /// Token balance.
+    pub balance: u128,
+}
+
+impl TokenHolding {
+    /// Encode as holding account data.
+    pub fn encode(&self) -> Data {
+        Data::from(&token_core::TokenHolding::Fungible {
+            definition_id: self.definition_id,
+            balance: self.balance,
+        })
+    }
+
+    /// Decode holding data. Returns `None` if the data is not a fungible
+    /// token holding.
+    pub fn decode(data: &Data) -> Option<Self> {
+        match token_core::TokenHolding::try_from(data).ok()? {
+            token_core::TokenHolding::Fungible {
+                definition_id,
+                balance,
+            } => Some(Self {
+                definition_id,
+                balance,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Longest token definition name CreateVault accepts, in bytes.
+pub const MAX_TOKEN_NAME_LEN: usize = 6;
+
+/// Check a token name: 1 to [`MAX_TOKEN_NAME_LEN`] bytes.
+pub fn validate_token_name(name: &str) -> Result<(), TreasuryError> {
+    if name.is_empty() || name.len() > MAX_TOKEN_NAME_LEN {
+        return Err(TreasuryError::InvalidTokenName);
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+/// Reasons the Treasury program rejects an instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TreasuryError {
+    /// The instruction was given the wrong number of accounts.
+    InvalidAccountCount { expected: usize, actual: usize },
+    /// An account expected to be a vault holding is not a token holding.
+    InvalidVaultHolding,
+    /// The vault is not registered in the treasury state.
+    UnknownVault,
+    /// A share amount is zero or exceeds the outstanding shares.
+    InvalidShareAmount,
+    /// A required signature is missing.
+    Unauthorized,
+    /// An arithmetic operation overflowed.
+    Overflow,
+    /// The treasury has already been initialized.
+    AlreadyInitialized,
+    /// The treasury has not been initialized.
+    NotInitialized,
+    /// An account is not the one the instruction expects.
+    UnexpectedAccount,
+    /// Schedule parameters are out of range.
+    InvalidSchedule,
+    /// The clock account does not hold a timestamp.
+    InvalidClock,
+    /// No scheduled installment is due yet.
+    NothingDue,
+    /// The escrow has already been settled.
+    EscrowClosed,
+    /// The escrow deadline has not passed yet.
+    DeadlineNotReached,
+    /// An amount that must be positive is zero.
+    ZeroAmount,
+    /// Grant milestones are empty, too many, or zero.
+    InvalidGrant,
+    /// The milestone is not the grant's next pending milestone.
+    InvalidMilestone,
+    /// The grant has no pending milestones left.
+    GrantClosed,
+    /// Minting would take the token's supply past the vault's cap.
+    SupplyCapExceeded,
+    /// The token definition is not the treasury's PDA for the vault, so the
+    /// treasury cannot authorize a mint.
+    NotMintable,
+    /// A swap or liquidity change has no slippage bound.
+    InvalidSlippage,
+    /// No AMM program has been set with `SetAmmProgram`.
+    AmmNotSet,
+    /// A memo or invoice reference is empty or longer than [`MAX_MEMO_LEN`]
+    /// bytes.
+    InvalidMemo,
+    /// The invoice has already been paid.
+    InvoicePaid,
+    /// The invoice's due time has passed.
+    InvoiceOverdue,
+    /// A payment doesn't match the amount due.
+    WrongPaymentAmount { due: u128, paid: u128 },
+    /// A split configuration is malformed, or the token has none.
+    InvalidSplit,
+    /// A holding has too little to cover a transfer.
+    InsufficientBalance,
+    /// The vault is frozen.
+    VaultFrozen,
+    /// Treasury metadata is out of bounds.
+    InvalidMetadata,
+    /// A token name is empty or longer than [`MAX_TOKEN_NAME_LEN`] bytes.
+    InvalidTokenName,
+    /// A token's display name, ticker or decimals are out of bounds.
+    InvalidTokenMetadata,
+    /// Account data would exceed the largest size an account can hold.
+    AccountDataTooLarge,
+}
+
+impl core::fmt::Display for TreasuryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidAccountCount { expected, actual } => {
+                write!(f, "expected {expected} accounts, got {actual}")
+            }
+            Self::InvalidVaultHolding => write!(f, "vault account is not a token holding"),
+            Self::UnknownVault => write!(f, "vault is not registered in the treasury"),
+            Self::InvalidShareAmount => write!(f, "invalid share amount"),
+            Self::Unauthorized => write!(f, "missing required authorization"),
+            Self::Overflow => write!(f, "arithmetic overflow"),
+            Self::AlreadyInitialized => write!(f, "treasury is already initialized"),
+            Self::NotInitialized => write!(f, "treasury is not initialized"),
+            Self::UnexpectedAccount => write!(f, "unexpected account"),
+            Self::InvalidSchedule => write!(f, "invalid payment schedule"),
+            Self::InvalidClock => write!(f, "clock account does not hold a timestamp"),
+            Self::NothingDue => write!(f, "no scheduled payment is due"),
+            Self::EscrowClosed => write!(f, "escrow is already settled"),
+            Self::DeadlineNotReached => write!(f, "escrow deadline has not passed"),
+            Self::ZeroAmount => write!(f, "amount must be non-zero"),
+            Self::InvalidGrant => write!(
+                f,
+                "a grant needs 1 to {MAX_MILESTONES} milestones, each non-zero"
+            ),
+            Self::InvalidMilestone => write!(f, "milestone is not the next pending milestone"),
+            Self::GrantClosed => write!(f, "grant has no pending milestones"),
+            Self::SupplyCapExceeded => write!(f, "mint would exceed the vault's supply cap"),
+            Self::NotMintable => write!(f, "token definition was not created by the treasury"),
+            Self::InvalidSlippage => write!(f, "minimum output must be non-zero"),
+            Self::AmmNotSet => write!(f, "no AMM program set for the treasury"),
+            Self::InvalidMemo => write!(f, "a memo must be 1 to {MAX_MEMO_LEN} bytes"),
+            Self::InvoicePaid => write!(f, "invoice is already paid"),
+            Self::InvoiceOverdue => write!(f, "invoice is past its due time"),
+            Self::WrongPaymentAmount { due, paid } => {
+                write!(f, "invoice is for {due}, payment is {paid}")
+            }
+            Self::InvalidSplit => write!(
+                f,
+                "a split needs 1 to {MAX_SPLIT_TARGETS} distinct labeled targets whose shares add \
+                 up to {BPS_DENOMINATOR} bps, one of them the dust target"
+            ),
+            Self::InsufficientBalance => write!(f, "insufficient balance"),
+            Self::VaultFrozen => write!(f, "vault is frozen"),
+            Self::InvalidMetadata => write!(
+                f,
+                "metadata needs a name of 1 to {MAX_METADATA_NAME_LEN} bytes, a URI of at most \
+                 {MAX_METADATA_URI_LEN} bytes and at most {MAX_METADATA_TAGS} distinct tags of \
+                 1 to {MAX_METADATA_TAG_LEN} bytes"
+            ),
+            Self::InvalidTokenName => {
+                write!(f, "token name must be 1 to {MAX_TOKEN_NAME_LEN} bytes")
+            }
+            Self::InvalidTokenMetadata => write!(
+                f,
+                "token metadata needs a display name of 1 to {MAX_DISPLAY_NAME_LEN} bytes, a \
+                 ticker of 1 to {MAX_TICKER_LEN} bytes and at most {MAX_DECIMALS} decimals"
+            ),
+            Self::AccountDataTooLarge => write!(f, "account data is too large"),
+        }
+    }
+}
+
+impl std::error::Error for TreasuryError {}
+
+// ---------------------------------------------------------------------------
+// PDA derivation helpers
+// ---------------------------------------------------------------------------
+
+/// Identifies one treasury among the many a single program deployment can
+/// host. Every PDA of a treasury, its state included, is derived from it.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct TreasuryId([u8; 32]);
+
+impl TreasuryId {
+    /// A treasury ID from a raw seed.
+    pub const fn new(seed: [u8; 32]) -> Self {
+        Self(seed)
+    }
+
+    /// The ID of the treasury `creator` created under a human-readable name,
+    /// such as a team's. Names are scoped to their creator, so two creators
+    /// picking the same name get different treasuries.
+    pub fn from_name(creator: &AccountId, name: &str) -> Self {
+        Self(tagged_seed(b"treasury_id", &[creator.value(), name.as_bytes()]))
+    }
+
+    pub fn value(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
 /// Compute the treasury state PDA account ID.
-pub fn compute_treasury_state_pda(treasury_program_id: &ProgramId) -> AccountId {
-    AccountId::from((treasury_program_id, &treasury_state_pda_seed()))
+pub fn compute_treasury_state_pda(
+    treasury_program_id: &ProgramId,
+    treasury: &TreasuryId,
+) -> AccountId {
+    AccountId::from((treasury_program_id, &treasury_state_pda_seed(treasury)))
 }
 
 /// Compute the vault holding PDA for a given token definition.
 pub fn compute_vault_holding_pda(
     treasury_program_id: &ProgramId,
+    treasury: &TreasuryId,
+    token_definition_id: &AccountId,
+) -> AccountId {
+    AccountId::from((
+        treasury_program_id,
+        &vault_holding_pda_seed(treasury, token_definition_id),
+    ))
+}
+
+/// Compute the share definition PDA for a vault's token definition.
+pub fn compute_share_definition_pda(
+    treasury_program_id: &ProgramId,
+    treasury: &TreasuryId,
+    token_definition_id: &AccountId,
+) -> AccountId {
+    AccountId::from((
+        treasury_program_id,
+        &share_definition_pda_seed(treasury, token_definition_id),
+    ))
+}
+
+/// Compute the token definition PDA for the vault at registry position
+/// `index`. A vault created with this definition can be minted into with
+/// MintMore.
+pub fn compute_token_definition_pda(
+    treasury_program_id: &ProgramId,
+    treasury: &TreasuryId,
+    index: u64,
+) -> AccountId {
+    AccountId::from((treasury_program_id, &token_definition_pda_seed(treasury, index)))
+}
+
+/// Compute the PDA of a vault's `schedule`-th payment schedule.
+pub fn compute_schedule_pda(
+    treasury_program_id: &ProgramId,
+    treasury: &TreasuryId,
+    token_definition_id: &AccountId,
+    schedule: u64,
+) -> AccountId {
+    AccountId::from((
+        treasury_program_id,
+        &schedule_pda_seed(treasury, token_definition_id, schedule),
+    ))
+}
+
+/// Compute the PDA holding an escrow's terms.
+pub fn compute_escrow_pda(
+    treasury_program_id: &ProgramId,
+    treasury: &TreasuryId,
+    escrow: u64,
+) -> AccountId {
+    AccountId::from((treasury_program_id, &escrow_pda_seed(treasury, escrow)))
+}
+
+/// Compute the token holding PDA that holds an escrow's funds.
+pub fn compute_escrow_holding_pda(
+    treasury_program_id: &ProgramId,
+    treasury: &TreasuryId,
+    escrow: u64,
+) -> AccountId {
+    AccountId::from((treasury_program_id, &escrow_holding_pda_seed(treasury, escrow)))
+}
+
+/// Compute the PDA holding a grant's milestones.
+pub fn compute_grant_pda(
+    treasury_program_id: &ProgramId,
+    treasury: &TreasuryId,
+    grant: u64,
+) -> AccountId {
+    AccountId::from((treasury_program_id, &grant_pda_seed(treasury, grant)))
+}
+
+/// Compute the token holding PDA that holds a grant's unreleased funds.
+pub fn compute_grant_holding_pda(
+    treasury_program_id: &ProgramId,
+    treasury: &TreasuryId,
+    grant: u64,
+) -> AccountId {
+    AccountId::from((treasury_program_id, &grant_holding_pda_seed(treasury, grant)))
+}
+
+/// Compute the token holding PDA of a token's vault labeled `label`. Split
+/// deposits are paid into labeled vaults.
+pub fn compute_labeled_vault_pda(
+    treasury_program_id: &ProgramId,
+    treasury: &TreasuryId,
+    token_definition_id: &AccountId,
+    label: &str,
+) -> AccountId {
+    AccountId::from((
+        treasury_program_id,
+        &labeled_vault_pda_seed(treasury, token_definition_id, label),
+    ))
+}
+
+/// Compute the holding PDA of a token's vault: the main vault for `None`,
+/// otherwise the vault with that label.
+pub fn compute_vault_pda(
+    treasury_program_id: &ProgramId,
+    treasury: &TreasuryId,
     token_definition_id: &AccountId,
+    label: Option<&str>,
+) -> AccountId {
+    AccountId::from((
+        treasury_program_id,
+        &vault_pda_seed(treasury, token_definition_id, label),
+    ))
+}
+
+/// Compute the PDA holding an invoice.
+pub fn compute_invoice_pda(
+    treasury_program_id: &ProgramId,
+    treasury: &TreasuryId,
+    invoice: u64,
+) -> AccountId {
+    AccountId::from((treasury_program_id, &invoice_pda_seed(treasury, invoice)))
+}
+
+/// Compute the PDA of the `page`-th ledger page.
+pub fn compute_ledger_page_pda(
+    treasury_program_id: &ProgramId,
+    treasury: &TreasuryId,
+    page: u64,
 ) -> AccountId {
-    AccountId::from((treasury_program_id, &vault_holding_pda_seed(token_definition_id)))
+    AccountId::from((treasury_program_id, &ledger_page_pda_seed(treasury, page)))
+}
+
+/// Compute the PDA holding the treasury's metadata.
+pub fn compute_metadata_pda(treasury_program_id: &ProgramId, treasury: &TreasuryId) -> AccountId {
+    AccountId::from((treasury_program_id, &metadata_pda_seed(treasury)))
 }
 
 /// Build the PdaSeed for treasury state.
-pub fn treasury_state_pda_seed() -> PdaSeed {
-    PdaSeed::new(TREASURY_STATE_SEED)
+pub fn treasury_state_pda_seed(treasury: &TreasuryId) -> PdaSeed {
+    PdaSeed::new(tagged_seed(b"treasury_state", &[treasury.value()]))
 }
 
 /// Build the PdaSeed for a vault holding PDA.
-pub fn vault_holding_pda_seed(token_definition_id: &AccountId) -> PdaSeed {
-    PdaSeed::new(*token_definition_id.value())
+pub fn vault_holding_pda_seed(treasury: &TreasuryId, token_definition_id: &AccountId) -> PdaSeed {
+    PdaSeed::new(tagged_seed(
+        b"vault_holding",
+        &[treasury.value(), token_definition_id.value()],
+    ))
+}
+
+/// Build the PdaSeed for a vault's share definition PDA.
+pub fn share_definition_pda_seed(
+    treasury: &TreasuryId,
+    token_definition_id: &AccountId,
+) -> PdaSeed {
+    PdaSeed::new(tagged_seed(
+        b"share_definition",
+        &[treasury.value(), token_definition_id.value()],
+    ))
+}
+
+/// Build the PdaSeed for a treasury-minted token definition.
+pub fn token_definition_pda_seed(treasury: &TreasuryId, index: u64) -> PdaSeed {
+    PdaSeed::new(tagged_seed(
+        b"token_definition",
+        &[treasury.value(), &index.to_le_bytes()],
+    ))
+}
+
+/// Build the PdaSeed for a vault's payment schedule PDA.
+pub fn schedule_pda_seed(
+    treasury: &TreasuryId,
+    token_definition_id: &AccountId,
+    schedule: u64,
+) -> PdaSeed {
+    PdaSeed::new(tagged_seed(
+        b"schedule",
+        &[
+            treasury.value(),
+            token_definition_id.value(),
+            &schedule.to_le_bytes(),
+        ],
+    ))
+}
+
+/// Build the PdaSeed for an escrow's terms PDA.
+pub fn escrow_pda_seed(treasury: &TreasuryId, escrow: u64) -> PdaSeed {
+    PdaSeed::new(tagged_seed(b"escrow", &[treasury.value(), &escrow.to_le_bytes()]))
+}
+
+/// Build the PdaSeed for an escrow's token holding PDA.
+pub fn escrow_holding_pda_seed(treasury: &TreasuryId, escrow: u64) -> PdaSeed {
+    PdaSeed::new(tagged_seed(
+        b"escrow_holding",
+        &[treasury.value(), &escrow.to_le_bytes()],
+    ))
+}
+
+/// Build the PdaSeed for a grant's milestone PDA.
+pub fn grant_pda_seed(treasury: &TreasuryId, grant: u64) -> PdaSeed {
+    PdaSeed::new(tagged_seed(b"grant", &[treasury.value(), &grant.to_le_bytes()]))
+}
+
+/// Build the PdaSeed for a grant's token holding PDA.
+pub fn grant_holding_pda_seed(treasury: &TreasuryId, grant: u64) -> PdaSeed {
+    PdaSeed::new(tagged_seed(
+        b"grant_holding",
+        &[treasury.value(), &grant.to_le_bytes()],
+    ))
+}
+
+/// Build the PdaSeed for a token's labeled vault holding PDA.
+pub fn labeled_vault_pda_seed(
+    treasury: &TreasuryId,
+    token_definition_id: &AccountId,
+    label: &str,
+) -> PdaSeed {
+    PdaSeed::new(tagged_seed(
+        b"labeled_vault",
+        &[treasury.value(), token_definition_id.value(), label.as_bytes()],
+    ))
+}
+
+/// Build the PdaSeed for a token's main vault (`None`) or labeled vault.
+pub fn vault_pda_seed(
+    treasury: &TreasuryId,
+    token_definition_id: &AccountId,
+    label: Option<&str>,
+) -> PdaSeed {
+    match label {
+        Some(label) => labeled_vault_pda_seed(treasury, token_definition_id, label),
+        None => vault_holding_pda_seed(treasury, token_definition_id),
+    }
+}
+
+/// Build the PdaSeed for an invoice PDA.
+pub fn invoice_pda_seed(treasury: &TreasuryId, invoice: u64) -> PdaSeed {
+    PdaSeed::new(tagged_seed(b"invoice", &[treasury.value(), &invoice.to_le_bytes()]))
+}
+
+/// Build the PdaSeed for the `page`-th ledger page.
+pub fn ledger_page_pda_seed(treasury: &TreasuryId, page: u64) -> PdaSeed {
+    PdaSeed::new(tagged_seed(b"ledger_page", &[treasury.value(), &page.to_le_bytes()]))
+}
+
+/// Build the PdaSeed for the metadata PDA.
+pub fn metadata_pda_seed(treasury: &TreasuryId) -> PdaSeed {
+    PdaSeed::new(tagged_seed(b"metadata", &[treasury.value()]))
+}
+
+/// Hash a domain tag and seed parts into a 32-byte PDA seed, so seeds for
+/// different account kinds can never collide.
+fn tagged_seed(tag: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"treasury/");
+    hasher.update(tag);
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_deposit_mints_shares_one_to_one() {
+        assert_eq!(shares_for_deposit(250, 0, 0), Ok(250));
+        assert_eq!(shares_for_deposit(250, 1000, 0), Ok(250));
+    }
+
+    #[test]
+    fn deposits_mint_shares_pro_rata_rounding_down() {
+        assert_eq!(shares_for_deposit(100, 1000, 500), Ok(50));
+        assert_eq!(shares_for_deposit(1, 3, 1), Ok(0));
+    }
+
+    #[test]
+    fn deposits_into_an_empty_vault_with_shares_outstanding_fail() {
+        assert_eq!(shares_for_deposit(100, 0, 500), Err(TreasuryError::InvalidShareAmount));
+    }
+
+    #[test]
+    fn share_math_reports_overflow() {
+        assert_eq!(shares_for_deposit(u128::MAX, 1, 2), Err(TreasuryError::Overflow));
+        assert_eq!(redemption_amount(2, u128::MAX, 2), Err(TreasuryError::Overflow));
+    }
+
+    #[test]
+    fn redemptions_pay_pro_rata_rounding_down() {
+        assert_eq!(redemption_amount(50, 1100, 500), Ok(110));
+        assert_eq!(redemption_amount(500, 1100, 500), Ok(1100));
+        assert_eq!(redemption_amount(1, 2, 3), Ok(0));
+    }
+
+    #[test]
+    fn redemptions_of_more_than_the_outstanding_shares_fail() {
+        assert_eq!(redemption_amount(501, 1100, 500), Err(TreasuryError::InvalidShareAmount));
+    }
+
+    #[test]
+    fn a_deposit_redeemed_at_once_never_pays_out_more() {
+        let (balance, total_shares, amount) = (1000, 700, 333);
+        let shares = shares_for_deposit(amount, balance, total_shares).unwrap();
+        let payout = redemption_amount(shares, balance + amount, total_shares + shares).unwrap();
+        assert!(payout <= amount);
+    }
+
+    fn schedule(start: u64, interval: u64, count: u64) -> Schedule {
+        Schedule {
+            token_definition_id: AccountId::new([1; 32]),
+            recipient: AccountId::new([2; 32]),
+            amount: 100,
+            interval,
+            start,
+            count,
+            paid: 0,
+        }
+    }
+
+    #[test]
+    fn schedules_fall_due_one_installment_per_interval() {
+        let schedule = schedule(1_000, 10, 3);
+        assert_eq!(schedule.due_count(0), 0);
+        assert_eq!(schedule.due_count(999), 0);
+        assert_eq!(schedule.due_count(1_000), 1);
+        assert_eq!(schedule.due_count(1_009), 1);
+        assert_eq!(schedule.due_count(1_010), 2);
+        assert_eq!(schedule.due_count(1_020), 3);
+    }
+
+    #[test]
+    fn schedules_never_fall_due_past_their_count() {
+        assert_eq!(schedule(1_000, 10, 3).due_count(1_030), 3);
+        assert_eq!(schedule(1_000, 10, 3).due_count(u64::MAX), 3);
+        // The installment count saturates instead of overflowing
+        assert_eq!(schedule(0, 1, u64::MAX).due_count(u64::MAX), u64::MAX);
+    }
+
+    fn ledger_entry(seq: u64) -> LedgerEntry {
+        LedgerEntry {
+            kind: LedgerKind::Send,
+            vault: AccountId::new([1; 32]),
+            counterparty: AccountId::new([2; 32]),
+            amount: u128::MAX,
+            seq,
+        }
+    }
+
+    #[test]
+    fn ledger_entries_round_trip() {
+        let entry = ledger_entry(7);
+        let data = entry.encode();
+        assert_eq!(data.len(), LEDGER_ENTRY_SIZE);
+        assert_eq!(LedgerEntry::decode(&data), Some(entry));
+    }
+
+    #[test]
+    fn ledger_entries_of_the_wrong_size_do_not_decode() {
+        let data = ledger_entry(0).encode();
+        assert_eq!(LedgerEntry::decode(&data[..LEDGER_ENTRY_SIZE - 1]), None);
+        assert_eq!(LedgerEntry::decode(&[data.clone(), vec![0]].concat()), None);
+    }
+
+    #[test]
+    fn ledger_entries_with_an_unknown_kind_do_not_decode() {
+        let mut data = ledger_entry(0).encode();
+        data[0] = 0xff;
+        assert_eq!(LedgerEntry::decode(&data), None);
+    }
+
+    #[test]
+    fn ledger_pages_read_back_in_order() {
+        let first: Vec<u8> = (0..2).flat_map(|seq| ledger_entry(seq).encode()).collect();
+        let second = ledger_entry(2).encode();
+        assert_eq!(ledger_page_len(&first), Some(2));
+        assert_eq!(ledger_page_len(&first[1..]), None);
+
+        let entries: Vec<_> = ledger_entries([first.as_slice(), second.as_slice()]).collect();
+        assert_eq!(entries.iter().map(|entry| entry.seq).collect::<Vec<_>>(), [0, 1, 2]);
+    }
+
+    fn split(targets: &[(&str, u16)], dust_label: &str) -> SplitConfig {
+        SplitConfig {
+            targets: targets
+                .iter()
+                .map(|(label, bps)| SplitTarget {
+                    label: label.to_string(),
+                    bps: *bps,
+                })
+                .collect(),
+            dust_label: dust_label.to_string(),
+        }
+    }
+
+    #[test]
+    fn split_amounts_round_down_and_give_the_dust_to_the_dust_target() {
+        let config = split(&[("ops", 3333), ("grants", 3333), ("reserve", 3334)], "reserve");
+        assert_eq!(config.amounts(10), Ok(vec![3, 3, 4]));
+        assert_eq!(config.amounts(10_000), Ok(vec![3333, 3333, 3334]));
+        assert_eq!(config.amounts(0), Ok(vec![0, 0, 0]));
+    }
+
+    #[test]
+    fn split_amounts_add_up_for_the_largest_amount() {
+        let config = split(&[("a", 5000), ("b", 5000)], "a");
+        let amounts = config.amounts(u128::MAX).unwrap();
+        assert_eq!(amounts[0].checked_add(amounts[1]), Some(u128::MAX));
+        assert_eq!(amounts[0] - amounts[1], 1);
+    }
+
+    #[test]
+    fn splits_with_shares_over_the_whole_are_invalid() {
+        let config = split(&[("a", 6000), ("b", 6000)], "a");
+        assert_eq!(config.validate(), Err(TreasuryError::InvalidSplit));
+    }
 }
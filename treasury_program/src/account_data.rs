@@ -0,0 +1,16 @@
+//! Account data encoding shared by the handlers.
+
+use borsh::BorshSerialize;
+use nssa_core::account::Data;
+use treasury_core::TreasuryError;
+
+/// Borsh-encode `value` as account data.
+///
+/// Fails if the encoding exceeds the account size limit, as a treasury state
+/// with a long enough registry eventually does, rather than panicking.
+pub fn encode<T: BorshSerialize + ?Sized>(value: &T) -> Result<Data, TreasuryError> {
+    borsh::to_vec(value)
+        .ok()
+        .and_then(|data| Data::try_from(data).ok())
+        .ok_or(TreasuryError::AccountDataTooLarge)
+}
@@ -0,0 +1,95 @@
+//! AMM program instruction encoding and pool checks shared by the handlers.
+//!
+//! Instructions are the AMM program's own `amm_core::Instruction`, which
+//! `ChainedCall::new` serializes the way the AMM reads it. Pool accounts are
+//! always passed in pool order: the pool, its token A and B vaults, (for
+//! liquidity) the LP token definition, then the caller's A and B holdings
+//! (and LP holding).
+
+use nssa_core::account::{AccountId, AccountWithMetadata};
+use nssa_core::program::{ChainedCall, ProgramId};
+use treasury_core::{TreasuryError, TreasuryState};
+
+/// The AMM program pinned in `state`, after checking that it owns `pool`.
+pub fn pinned_program(
+    state: &TreasuryState,
+    pool: &AccountWithMetadata,
+) -> Result<ProgramId, TreasuryError> {
+    let amm_program_id = state.amm_program_id.ok_or(TreasuryError::AmmNotSet)?;
+    if pool.account.program_owner != amm_program_id {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    Ok(amm_program_id)
+}
+
+/// The LP token definition recorded in an AMM pool account.
+pub fn pool_lp_definition(pool: &AccountWithMetadata) -> Option<AccountId> {
+    amm_core::PoolDefinition::try_from(&pool.account.data)
+        .ok()
+        .map(|definition| definition.liquidity_pool_id)
+}
+
+/// AMM `Swap` of `amount_in` of `token_definition_in` for at least
+/// `min_amount_out` of the pool's other token. Accounts: `[pool,
+/// pool_vault_a, pool_vault_b, holding_a, holding_b]`, with the paying
+/// holding authorized.
+pub fn swap_call(
+    amm_program_id: &ProgramId,
+    pre_states: Vec<AccountWithMetadata>,
+    amount_in: u128,
+    min_amount_out: u128,
+    token_definition_in: AccountId,
+) -> ChainedCall {
+    ChainedCall::new(
+        *amm_program_id,
+        pre_states,
+        &amm_core::Instruction::Swap {
+            swap_amount_in: amount_in,
+            min_amount_out,
+            token_definition_id_in: token_definition_in,
+        },
+    )
+}
+
+/// AMM `AddLiquidity` of up to `max_amount_a` and `max_amount_b` for at
+/// least `min_amount_lp` LP tokens. Accounts: `[pool, pool_vault_a,
+/// pool_vault_b, pool_lp_definition, holding_a, holding_b, lp_holding]`, with
+/// holdings A and B authorized.
+pub fn add_liquidity_call(
+    amm_program_id: &ProgramId,
+    pre_states: Vec<AccountWithMetadata>,
+    min_amount_lp: u128,
+    max_amount_a: u128,
+    max_amount_b: u128,
+) -> ChainedCall {
+    ChainedCall::new(
+        *amm_program_id,
+        pre_states,
+        &amm_core::Instruction::AddLiquidity {
+            min_amount_liquidity: min_amount_lp,
+            max_amount_to_add_token_a: max_amount_a,
+            max_amount_to_add_token_b: max_amount_b,
+        },
+    )
+}
+
+/// AMM `RemoveLiquidity` of `lp_amount` LP tokens for at least `min_amount_a`
+/// and `min_amount_b`. Accounts as for [`add_liquidity_call`], with the LP
+/// holding authorized.
+pub fn remove_liquidity_call(
+    amm_program_id: &ProgramId,
+    pre_states: Vec<AccountWithMetadata>,
+    lp_amount: u128,
+    min_amount_a: u128,
+    min_amount_b: u128,
+) -> ChainedCall {
+    ChainedCall::new(
+        *amm_program_id,
+        pre_states,
+        &amm_core::Instruction::RemoveLiquidity {
+            remove_liquidity_amount: lp_amount,
+            min_amount_to_remove_token_a: min_amount_a,
+            min_amount_to_remove_token_b: min_amount_b,
+        },
+    )
+}
@@ -0,0 +1,78 @@
+//! Authorization checks shared by the handlers.
+
+use nssa_core::account::{AccountId, AccountWithMetadata};
+use nssa_core::program::PdaSeed;
+use treasury_core::{Role, TreasuryError, TreasuryId, TreasuryState, compute_treasury_state_pda};
+
+/// Require `signer` to be the treasury admin and to have signed.
+pub fn require_admin(
+    state: &TreasuryState,
+    signer: &AccountWithMetadata,
+) -> Result<(), TreasuryError> {
+    let admin = state.admin.ok_or(TreasuryError::NotInitialized)?;
+    if admin != signer.account_id || !signer.is_authorized {
+        return Err(TreasuryError::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Require `signer` to hold `role` and to have signed.
+pub fn require_role(
+    state: &TreasuryState,
+    role: Role,
+    signer: &AccountWithMetadata,
+) -> Result<(), TreasuryError> {
+    if !state.has_role(role, &signer.account_id) || !signer.is_authorized {
+        return Err(TreasuryError::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Require `signer` to be the treasury admin or to hold `role`, and to have
+/// signed.
+pub fn require_admin_or_role(
+    state: &TreasuryState,
+    role: Role,
+    signer: &AccountWithMetadata,
+) -> Result<(), TreasuryError> {
+    if state.admin == Some(signer.account_id) && signer.is_authorized {
+        return Ok(());
+    }
+    require_role(state, role, signer)
+}
+
+/// Require `account_id` to be the PDA derived from `seed` under the program
+/// owning `owned`.
+///
+/// A program does not know its own ID, so it is taken from an account the
+/// handler writes to: the runtime rejects writes to accounts the program does
+/// not own, so that owner is this program whenever the instruction succeeds.
+pub fn require_pda(
+    account_id: &AccountId,
+    owned: &AccountWithMetadata,
+    seed: &PdaSeed,
+) -> Result<(), TreasuryError> {
+    if *account_id != AccountId::from((&owned.account.program_owner, seed)) {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    Ok(())
+}
+
+/// Require `state` to be the state PDA of `treasury` under the program owning
+/// it, and `account_id` to be derived from `seed` under that same program.
+///
+/// For handlers that do not write the state. The caller must then authorize
+/// `account_id` with `seed` in a chained call: the runtime only accepts that
+/// if this program derived it, which proves the state's owner is this
+/// program and the state is genuine.
+pub fn require_state_pda(
+    state: &AccountWithMetadata,
+    treasury: &TreasuryId,
+    account_id: &AccountId,
+    seed: &PdaSeed,
+) -> Result<(), TreasuryError> {
+    if state.account_id != compute_treasury_state_pda(&state.account.program_owner, treasury) {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    require_pda(account_id, state, seed)
+}
@@ -0,0 +1,80 @@
+//! Handler for Burn — burns tokens held by a treasury vault.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::AccountWithMetadata;
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use treasury_core::{LedgerKind, TokenHolding, TreasuryError, TreasuryState, vault_holding_pda_seed};
+
+use crate::account_data;
+use crate::auth::require_admin;
+use crate::ledger;
+use crate::token;
+
+/// Accounts: `[treasury_state, token_definition, vault_holding, admin,
+/// ledger_page]`. The admin must sign.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    amount: u128,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 5 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 5,
+            actual: accounts.len(),
+        });
+    }
+    if amount == 0 {
+        return Err(TreasuryError::ZeroAmount);
+    }
+
+    // Read data first to avoid borrow issues
+    let treasury_data = accounts[0].account.data.clone();
+    let token_def_data = accounts[1].account.clone();
+    let vault_data = accounts[2].account.clone();
+    let admin_data = accounts[3].account.clone();
+    let token_def_id = accounts[1].account_id;
+    let vault_id = accounts[2].account_id;
+
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    require_admin(&state, &accounts[3])?;
+
+    let vault_holding =
+        TokenHolding::decode(&vault_data.data).ok_or(TreasuryError::InvalidVaultHolding)?;
+    if vault_holding.definition_id != token_def_id {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    let vault = state
+        .vault_mut(&token_def_id)
+        .filter(|vault| vault.holding_id == vault_id)
+        .ok_or(TreasuryError::UnknownVault)?;
+    vault.require_active()?;
+    vault.burned = vault.burned.checked_add(amount).ok_or(TreasuryError::Overflow)?;
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::Burn,
+        vault_id,
+        token_def_id,
+        amount,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // The vault authorizes the burn through its PDA seed
+    let token_def_meta = AccountWithMetadata::new(token_def_data.clone(), false, token_def_id);
+    let vault_meta = AccountWithMetadata::new(vault_data.clone(), true, vault_id);
+    let burn_call = token::burn_call(token_program_id, vec![token_def_meta, vault_meta], amount)
+        .with_pda_seeds(vec![vault_holding_pda_seed(&state.treasury_id, &token_def_id)]);
+
+    // Build post_states
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let token_def_post = AccountPostState::new(token_def_data);
+    let vault_post = AccountPostState::new(vault_data);
+    let admin_post = AccountPostState::new(admin_data);
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![treasury_post, token_def_post, vault_post, admin_post, ledger_post],
+        chained_calls: vec![burn_call],
+    })
+}
@@ -0,0 +1,125 @@
+//! Handler for CreateEscrow — moves funds from a vault into an escrow PDA.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::{Account, AccountId, AccountWithMetadata};
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use treasury_core::{
+    Escrow, EscrowStatus, LedgerKind, TokenHolding, TreasuryError, TreasuryState,
+    escrow_holding_pda_seed, escrow_pda_seed, vault_holding_pda_seed,
+};
+
+use crate::account_data;
+use crate::auth::{require_admin, require_pda};
+use crate::ledger;
+use crate::token;
+
+/// Accounts: `[treasury_state, vault_holding, escrow, escrow_holding, admin,
+/// ledger_page]`.
+/// `vault_holding` must be `payer_vault`. The escrow and its holding must be
+/// the PDAs for the next escrow ID; the admin must sign.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    payer_vault: &AccountId,
+    beneficiary: &AccountId,
+    amount: u128,
+    arbiter: &AccountId,
+    deadline: u64,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 6 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 6,
+            actual: accounts.len(),
+        });
+    }
+
+    // Read data first to avoid borrow issues
+    let treasury_data = accounts[0].account.data.clone();
+    let vault_data = accounts[1].account.clone();
+    let escrow_data = accounts[2].account.clone();
+    let escrow_holding_data = accounts[3].account.clone();
+    let admin_data = accounts[4].account.clone();
+    let vault_id = accounts[1].account_id;
+    let escrow_id = accounts[2].account_id;
+    let escrow_holding_id = accounts[3].account_id;
+
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    require_admin(&state, &accounts[4])?;
+    if vault_id != *payer_vault {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+
+    let vault_holding =
+        TokenHolding::decode(&vault_data.data).ok_or(TreasuryError::InvalidVaultHolding)?;
+    state
+        .vault(&vault_holding.definition_id)
+        .filter(|vault| vault.holding_id == vault_id)
+        .ok_or(TreasuryError::UnknownVault)?
+        .require_active()?;
+    if amount == 0 {
+        return Err(TreasuryError::ZeroAmount);
+    }
+
+    // Escrow IDs are treasury-wide
+    let index = state.escrow_count;
+    require_pda(&escrow_id, &accounts[0], &escrow_pda_seed(&state.treasury_id, index))?;
+    if escrow_data != Account::default() {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    state.escrow_count = index.checked_add(1).ok_or(TreasuryError::Overflow)?;
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::CreateEscrow,
+        vault_id,
+        *beneficiary,
+        amount,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    let escrow = Escrow {
+        token_definition_id: vault_holding.definition_id,
+        vault_id,
+        beneficiary: *beneficiary,
+        arbiter: *arbiter,
+        amount,
+        deadline,
+        status: EscrowStatus::Open,
+    };
+    let mut escrow_account = escrow_data;
+    escrow_account.data = account_data::encode(&escrow)?;
+
+    // Fund the escrow holding from the vault. Both are treasury PDAs: the
+    // vault authorizes the transfer and the escrow holding is authorized so
+    // the Token program can claim it.
+    let vault_meta = AccountWithMetadata::new(vault_data.clone(), true, vault_id);
+    let escrow_holding_meta =
+        AccountWithMetadata::new(escrow_holding_data.clone(), true, escrow_holding_id);
+    let funding_call =
+        token::transfer_call(token_program_id, vec![vault_meta, escrow_holding_meta], amount)
+            .with_pda_seeds(vec![
+                vault_holding_pda_seed(&state.treasury_id, &vault_holding.definition_id),
+                escrow_holding_pda_seed(&state.treasury_id, index),
+            ]);
+
+    // Build post_states. The treasury claims the escrow PDA.
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let vault_post = AccountPostState::new(vault_data);
+    let escrow_post = AccountPostState::new_claimed(escrow_account);
+    let escrow_holding_post = AccountPostState::new(escrow_holding_data);
+    let admin_post = AccountPostState::new(admin_data);
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![
+            treasury_post,
+            vault_post,
+            escrow_post,
+            escrow_holding_post,
+            admin_post,
+            ledger_post,
+        ],
+        chained_calls: vec![funding_call],
+    })
+}
@@ -0,0 +1,128 @@
+//! Handler for CreateGrant — funds a milestone grant from a vault.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::{Account, AccountId, AccountWithMetadata};
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use treasury_core::{
+    Grant, LedgerKind, MAX_MILESTONES, Milestone, MilestoneState, TokenHolding, TreasuryError,
+    TreasuryState, grant_holding_pda_seed, grant_pda_seed, vault_holding_pda_seed,
+};
+
+use crate::account_data;
+use crate::auth::{require_admin, require_pda};
+use crate::ledger;
+use crate::token;
+
+/// Accounts: `[treasury_state, vault_holding, grant, grant_holding, admin,
+/// ledger_page]`.
+/// The grant and its holding must be the PDAs for the next grant ID; the
+/// admin must sign.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    grantee: &AccountId,
+    milestones: &[u128],
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 6 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 6,
+            actual: accounts.len(),
+        });
+    }
+    if milestones.is_empty()
+        || milestones.len() > MAX_MILESTONES
+        || milestones.contains(&0)
+    {
+        return Err(TreasuryError::InvalidGrant);
+    }
+    let total = milestones
+        .iter()
+        .try_fold(0u128, |total, amount| total.checked_add(*amount))
+        .ok_or(TreasuryError::Overflow)?;
+
+    // Read data first to avoid borrow issues
+    let treasury_data = accounts[0].account.data.clone();
+    let vault_data = accounts[1].account.clone();
+    let grant_data = accounts[2].account.clone();
+    let grant_holding_data = accounts[3].account.clone();
+    let admin_data = accounts[4].account.clone();
+    let vault_id = accounts[1].account_id;
+    let grant_id = accounts[2].account_id;
+    let grant_holding_id = accounts[3].account_id;
+
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    require_admin(&state, &accounts[4])?;
+
+    let vault_holding =
+        TokenHolding::decode(&vault_data.data).ok_or(TreasuryError::InvalidVaultHolding)?;
+    state
+        .vault(&vault_holding.definition_id)
+        .filter(|vault| vault.holding_id == vault_id)
+        .ok_or(TreasuryError::UnknownVault)?
+        .require_active()?;
+
+    // Grant IDs are treasury-wide
+    let index = state.grant_count;
+    require_pda(&grant_id, &accounts[0], &grant_pda_seed(&state.treasury_id, index))?;
+    if grant_data != Account::default() {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    state.grant_count = index.checked_add(1).ok_or(TreasuryError::Overflow)?;
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::CreateGrant,
+        vault_id,
+        *grantee,
+        total,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    let grant = Grant {
+        token_definition_id: vault_holding.definition_id,
+        vault_id,
+        grantee: *grantee,
+        milestones: milestones
+            .iter()
+            .map(|amount| Milestone {
+                amount: *amount,
+                state: MilestoneState::Pending,
+            })
+            .collect(),
+    };
+    let mut grant_account = grant_data;
+    grant_account.data = account_data::encode(&grant)?;
+
+    // Fund the grant holding from the vault. The grant holding is authorized
+    // so the Token program can claim it.
+    let vault_meta = AccountWithMetadata::new(vault_data.clone(), true, vault_id);
+    let grant_holding_meta =
+        AccountWithMetadata::new(grant_holding_data.clone(), true, grant_holding_id);
+    let funding_call =
+        token::transfer_call(token_program_id, vec![vault_meta, grant_holding_meta], total)
+            .with_pda_seeds(vec![
+                vault_holding_pda_seed(&state.treasury_id, &vault_holding.definition_id),
+                grant_holding_pda_seed(&state.treasury_id, index),
+            ]);
+
+    // Build post_states. The treasury claims the grant PDA.
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let vault_post = AccountPostState::new(vault_data);
+    let grant_post = AccountPostState::new_claimed(grant_account);
+    let grant_holding_post = AccountPostState::new(grant_holding_data);
+    let admin_post = AccountPostState::new(admin_data);
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![
+            treasury_post,
+            vault_post,
+            grant_post,
+            grant_holding_post,
+            admin_post,
+            ledger_post,
+        ],
+        chained_calls: vec![funding_call],
+    })
+}
@@ -0,0 +1,98 @@
+//! Handler for CreateSchedule — records a recurring payment from a vault.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::{Account, AccountId, AccountWithMetadata};
+use nssa_core::program::{AccountPostState, ProgramOutput};
+use treasury_core::{
+    LedgerKind, Schedule, TokenHolding, TreasuryError, TreasuryState, schedule_pda_seed,
+};
+
+use crate::account_data;
+use crate::auth::{require_admin, require_pda};
+use crate::ledger;
+
+/// Accounts: `[treasury_state, vault_holding, schedule, admin, ledger_page]`. The schedule
+/// must be the vault's next schedule PDA; the admin must sign.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    recipient: &AccountId,
+    amount: u128,
+    interval: u64,
+    start: u64,
+    count: u64,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 5 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 5,
+            actual: accounts.len(),
+        });
+    }
+    if amount == 0 || interval == 0 || count == 0 {
+        return Err(TreasuryError::InvalidSchedule);
+    }
+
+    // Read data first to avoid borrow issues
+    let treasury_data = accounts[0].account.data.clone();
+    let vault_data = accounts[1].account.clone();
+    let schedule_data = accounts[2].account.clone();
+    let admin_data = accounts[3].account.clone();
+    let vault_id = accounts[1].account_id;
+    let schedule_id = accounts[2].account_id;
+
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    require_admin(&state, &accounts[3])?;
+
+    let vault_holding =
+        TokenHolding::decode(&vault_data.data).ok_or(TreasuryError::InvalidVaultHolding)?;
+    let vault = state
+        .vault_mut(&vault_holding.definition_id)
+        .filter(|vault| vault.holding_id == vault_id)
+        .ok_or(TreasuryError::UnknownVault)?;
+    vault.require_active()?;
+
+    // Schedules are numbered per vault
+    let index = vault.schedule_count;
+    require_pda(
+        &schedule_id,
+        &accounts[0],
+        &schedule_pda_seed(&state.treasury_id, &vault_holding.definition_id, index),
+    )?;
+    if schedule_data != Account::default() {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    vault.schedule_count = index.checked_add(1).ok_or(TreasuryError::Overflow)?;
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::CreateSchedule,
+        vault_id,
+        *recipient,
+        amount,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    let schedule = Schedule {
+        token_definition_id: vault_holding.definition_id,
+        recipient: *recipient,
+        amount,
+        interval,
+        start,
+        count,
+        paid: 0,
+    };
+    let mut schedule_account = schedule_data;
+    schedule_account.data = account_data::encode(&schedule)?;
+
+    // Build post_states. The treasury claims the schedule PDA.
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let vault_post = AccountPostState::new(vault_data);
+    let schedule_post = AccountPostState::new_claimed(schedule_account);
+    let admin_post = AccountPostState::new(admin_data);
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![treasury_post, vault_post, schedule_post, admin_post, ledger_post],
+        chained_calls: vec![],
+    })
+}
@@ -2,84 +2,90 @@
 
 use borsh::BorshDeserialize;
 use nssa_core::account::AccountWithMetadata;
-use nssa_core::program::{AccountPostState, ChainedCall, InstructionData, PdaSeed, ProgramId, ProgramOutput};
-use treasury_core::TreasuryState;
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use treasury_core::{
+    LedgerKind, TokenMetadata, TreasuryError, TreasuryState, VaultRecord, vault_holding_pda_seed,
+};
 
-/// Token instruction: [0x00 || total_supply (16 bytes LE) || name (6 bytes)]
-fn build_token_instruction(total_supply: u128, name: &str) -> InstructionData {
-    let mut name_bytes = [0u8; 6];
-    for (i, byte) in name.as_bytes().iter().take(6).enumerate() {
-        name_bytes[i] = *byte;
-    }
-    
-    let mut instruction = vec![0u8; 23];
-    instruction[1..17].copy_from_slice(&total_supply.to_le_bytes());
-    instruction[17..].copy_from_slice(&name_bytes);
-    
-    // Convert bytes to u32 words
-    instruction
-        .chunks(4)
-        .map(|chunk| {
-            let mut word = [0u8; 4];
-            word.copy_from_slice(chunk);
-            u32::from_le_bytes(word)
-        })
-        .collect()
-}
+use crate::account_data;
+use crate::auth::require_admin;
+use crate::ledger;
+use crate::token;
 
+/// Accounts: `[treasury_state, token_definition, vault_holding, admin,
+/// ledger_page]`. The admin must sign.
+///
+/// The token name must be at most
+/// [`MAX_TOKEN_NAME_LEN`](treasury_core::MAX_TOKEN_NAME_LEN) bytes; the
+/// display name, ticker and decimals are recorded in the vault registry.
 pub fn handle(
     accounts: &mut [AccountWithMetadata],
     token_name: &str,
+    metadata: &TokenMetadata,
     initial_supply: u128,
     token_program_id: &ProgramId,
-) -> ProgramOutput {
-    if accounts.len() != 3 {
-        return ProgramOutput {
-            instruction_data: vec![],
-            pre_states: accounts.to_vec(),
-            post_states: vec![],
-            chained_calls: vec![],
-        };
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 5 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 5,
+            actual: accounts.len(),
+        });
     }
 
     // Read data from accounts first (avoid borrow issues)
     let treasury_data = accounts[0].account.data.clone();
     let token_def_data = accounts[1].account.clone();
     let vault_data = accounts[2].account.clone();
+    let admin_data = accounts[3].account.clone();
     let token_def_id = accounts[1].account_id;
     let vault_id = accounts[2].account_id;
 
     // Update treasury state
-    let mut state = TreasuryState::try_from_slice(&*treasury_data).unwrap_or_default();
-    state.vault_count += 1;
-    accounts[0].account.data = borsh::to_vec(&state).unwrap().try_into().unwrap();
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    require_admin(&state, &accounts[3])?;
+    metadata.validate()?;
+    state.vault_count = state.vault_count.checked_add(1).ok_or(TreasuryError::Overflow)?;
+    state.vaults.push(VaultRecord {
+        metadata: Some(metadata.clone()),
+        ..VaultRecord::new(token_def_id, vault_id)
+    });
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::CreateVault,
+        vault_id,
+        token_def_id,
+        initial_supply,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
 
-    // Build chained call to Token program
-    let instruction_data = build_token_instruction(initial_supply, token_name);
-    
     // For the chained call, we need AccountWithMetadata
     let token_def_meta = AccountWithMetadata::new(token_def_data.clone(), false, token_def_id);
     let vault_meta = AccountWithMetadata::new(vault_data.clone(), true, vault_id);
     
     // PDA seed for the vault
-    let vault_pda_seed = PdaSeed::new(*vault_id.value());
+    let vault_pda_seed = vault_holding_pda_seed(&state.treasury_id, &token_def_id);
     
-    let chained_call = ChainedCall {
-        program_id: *token_program_id,
-        instruction_data,
-        pre_states: vec![token_def_meta, vault_meta],
-        pda_seeds: vec![vault_pda_seed],
-    };
+    // Build chained call to Token program
+    let chained_call = token::new_definition_call(
+        token_program_id,
+        vec![token_def_meta, vault_meta],
+        token_name,
+        initial_supply,
+    )?
+    .with_pda_seeds(vec![vault_pda_seed]);
 
-    // Build post_states using AccountPostState
+    // Build post_states using AccountPostState. The token definition and
+    // vault are claimed by the Token program in the chained call.
     let treasury_post = AccountPostState::new(accounts[0].account.clone());
-    let token_def_post = AccountPostState::new_claimed(token_def_data);
-    let vault_post = AccountPostState::new_claimed(vault_data);
+    let token_def_post = AccountPostState::new(token_def_data);
+    let vault_post = AccountPostState::new(vault_data);
+    let admin_post = AccountPostState::new(admin_data);
 
-    ProgramOutput {
+    Ok(ProgramOutput {
         instruction_data: vec![],
         pre_states: accounts.to_vec(),
-        post_states: vec![treasury_post, token_def_post, vault_post],
+        post_states: vec![treasury_post, token_def_post, vault_post, admin_post, ledger_post],
         chained_calls: vec![chained_call],
-    }
+    })
 }
@@ -1,64 +1,145 @@
-//! Handler for Deposit — receives tokens from external sender into treasury vault.
+//! Handler for Deposit — receives tokens from external sender into treasury vault
+//! and mints the depositor vault shares.
 
-use nssa_core::account::AccountWithMetadata;
-use nssa_core::program::{AccountPostState, ChainedCall, InstructionData, ProgramId, ProgramOutput};
+use borsh::BorshDeserialize;
+use nssa_core::account::{Account, AccountId, AccountWithMetadata};
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use treasury_core::{
+    LedgerKind, TokenHolding, TreasuryError, TreasuryState, share_definition_pda_seed,
+    shares_for_deposit,
+};
 
-/// Token transfer instruction: [0x01 || amount (16 bytes LE)]
-fn build_transfer_instruction(amount: u128) -> InstructionData {
-    let mut instruction = vec![0u8; 17];
-    instruction[0] = 0x01; // Transfer instruction tag
-    instruction[1..17].copy_from_slice(&amount.to_le_bytes());
-    
-    instruction
-        .chunks(4)
-        .map(|chunk| {
-            let mut word = [0u8; 4];
-            word.copy_from_slice(chunk);
-            u32::from_le_bytes(word)
-        })
-        .collect()
-}
+use crate::account_data;
+use crate::ledger;
+use crate::token;
+
+/// Name of the share token created on a vault's first deposit.
+const SHARE_TOKEN_NAME: &str = "SHARES";
 
-pub fn handle(accounts: &mut [AccountWithMetadata], amount: u128, token_program_id: &ProgramId) -> ProgramOutput {
-    if accounts.len() != 3 {
-        return ProgramOutput {
-            instruction_data: vec![],
-            pre_states: accounts.to_vec(),
-            post_states: vec![],
-            chained_calls: vec![],
-        };
+/// Accounts: `[treasury_state, sender_holding, vault_holding, share_definition,
+/// depositor_share_holding, ledger_page]`.
+///
+/// With `private_sender` the ledger entry records a zero counterparty, so a
+/// privacy-preserving deposit doesn't publish who paid in.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    amount: u128,
+    token_program_id: &ProgramId,
+    memo: Option<&str>,
+    private_sender: bool,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 6 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 6,
+            actual: accounts.len(),
+        });
     }
 
     // Read data first to avoid borrow issues
-    let treasury_data = accounts[0].account.clone();
+    let treasury_data = accounts[0].account.data.clone();
     let sender_data = accounts[1].account.clone();
     let vault_data = accounts[2].account.clone();
+    let share_def_data = accounts[3].account.clone();
+    let share_holding_data = accounts[4].account.clone();
     let sender_id = accounts[1].account_id;
+    let sender_authorized = accounts[1].is_authorized;
     let vault_id = accounts[2].account_id;
+    let share_def_id = accounts[3].account_id;
+    let share_holding_id = accounts[4].account_id;
+    let share_holding_authorized = accounts[4].is_authorized;
+
+    let vault_holding =
+        TokenHolding::decode(&vault_data.data).ok_or(TreasuryError::InvalidVaultHolding)?;
+
+    // Mint shares pro-rata to the vault balance before this deposit
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    let vault = state
+        .vault_mut(&vault_holding.definition_id)
+        .filter(|vault| vault.holding_id == vault_id)
+        .ok_or(TreasuryError::UnknownVault)?;
+    vault.require_active()?;
+    if vault.share_supply == 0 {
+        // No depositor holds shares, so the treasury owns the whole vault:
+        // its stake is the existing balance. Re-basing it here also restarts
+        // share accounting after the vault was emptied.
+        vault.treasury_shares = vault_holding.balance;
+    }
+    let total_shares = vault.total_shares().ok_or(TreasuryError::Overflow)?;
+    let shares = shares_for_deposit(amount, vault_holding.balance, total_shares)?;
+    if shares == 0 {
+        return Err(TreasuryError::InvalidShareAmount);
+    }
+    vault.share_supply = vault
+        .share_supply
+        .checked_add(shares)
+        .ok_or(TreasuryError::Overflow)?;
+    vault.deposited = vault
+        .deposited
+        .checked_add(amount)
+        .ok_or(TreasuryError::Overflow)?;
+    vault.deposit_count = vault
+        .deposit_count
+        .checked_add(1)
+        .ok_or(TreasuryError::Overflow)?;
+    let counterparty = if private_sender {
+        AccountId::default()
+    } else {
+        sender_id
+    };
+    let ledger_post = ledger::append_with_memo(
+        &mut state,
+        accounts,
+        LedgerKind::Deposit,
+        vault_id,
+        counterparty,
+        amount,
+        memo,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
 
-    // Build chained call to Token program
     // Sender authorizes the transfer, vault receives
-    let instruction_data = build_transfer_instruction(amount);
-    
-    let sender_meta = AccountWithMetadata::new(sender_data.clone(), true, sender_id);
+    let sender_meta = AccountWithMetadata::new(sender_data.clone(), sender_authorized, sender_id);
     let vault_meta = AccountWithMetadata::new(vault_data.clone(), false, vault_id);
-    
-    let chained_call = ChainedCall {
-        program_id: *token_program_id,
-        instruction_data,
-        pre_states: vec![sender_meta, vault_meta],
-        pda_seeds: vec![],
-    };
+    let transfer_call =
+        token::transfer_call(token_program_id, vec![sender_meta, vault_meta], amount);
+
+    // The treasury authorizes the share definition PDA as minter. The first
+    // deposit creates the definition with the depositor's shares as supply.
+    let share_def_meta = AccountWithMetadata::new(share_def_data.clone(), true, share_def_id);
+    let share_holding_meta = AccountWithMetadata::new(
+        share_holding_data.clone(),
+        share_holding_authorized,
+        share_holding_id,
+    );
+    let share_accounts = vec![share_def_meta, share_holding_meta];
+    let mint_call = if share_def_data == Account::default() {
+        token::new_definition_call(token_program_id, share_accounts, SHARE_TOKEN_NAME, shares)?
+    } else {
+        token::mint_call(token_program_id, share_accounts, shares)
+    }
+    .with_pda_seeds(vec![share_definition_pda_seed(
+        &state.treasury_id,
+        &vault_holding.definition_id,
+    )]);
 
     // Build post_states
-    let treasury_post = AccountPostState::new(treasury_data);
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
     let sender_post = AccountPostState::new(sender_data);
     let vault_post = AccountPostState::new(vault_data);
+    let share_def_post = AccountPostState::new(share_def_data);
+    let share_holding_post = AccountPostState::new(share_holding_data);
 
-    ProgramOutput {
+    Ok(ProgramOutput {
         instruction_data: vec![],
         pre_states: accounts.to_vec(),
-        post_states: vec![treasury_post, sender_post, vault_post],
-        chained_calls: vec![chained_call],
-    }
+        post_states: vec![
+            treasury_post,
+            sender_post,
+            vault_post,
+            share_def_post,
+            share_holding_post,
+            ledger_post,
+        ],
+        chained_calls: vec![transfer_call, mint_call],
+    })
 }
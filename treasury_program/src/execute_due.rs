@@ -0,0 +1,125 @@
+//! Handler for ExecuteDue — pays the installments of a schedule that have
+//! fallen due and advances its cursor.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::AccountWithMetadata;
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use treasury_core::{
+    LedgerKind, Schedule, TokenHolding, TreasuryError, TreasuryState, clock_account_id,
+    compute_treasury_state_pda, read_clock, schedule_pda_seed, vault_holding_pda_seed,
+};
+
+use crate::account_data;
+use crate::auth::require_pda;
+use crate::ledger;
+use crate::token::pda_transfer_call;
+
+/// Accounts: `[treasury_state, vault_holding, schedule, recipient_holding,
+/// clock, ledger_page]`. No signer is required.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    schedule_index: u64,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 6 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 6,
+            actual: accounts.len(),
+        });
+    }
+
+    // Read data first to avoid borrow issues
+    let treasury_data = accounts[0].account.clone();
+    let vault_data = accounts[1].account.clone();
+    let schedule_data = accounts[2].account.clone();
+    let recipient_data = accounts[3].account.clone();
+    let clock_data = accounts[4].account.clone();
+    let treasury_id = accounts[0].account_id;
+    let vault_id = accounts[1].account_id;
+    let schedule_id = accounts[2].account_id;
+    let recipient_id = accounts[3].account_id;
+    let clock_id = accounts[4].account_id;
+
+    let vault_holding =
+        TokenHolding::decode(&vault_data.data).ok_or(TreasuryError::InvalidVaultHolding)?;
+
+    // The schedule is written below, so its owner is this program. That
+    // proves both the schedule and the treasury state are ours.
+    let mut state = TreasuryState::try_from_slice(&treasury_data.data).unwrap_or_default();
+    require_pda(
+        &schedule_id,
+        &accounts[2],
+        &schedule_pda_seed(&state.treasury_id, &vault_holding.definition_id, schedule_index),
+    )?;
+    let owner = &schedule_data.program_owner;
+    if treasury_id != compute_treasury_state_pda(owner, &state.treasury_id) {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    state
+        .vault(&vault_holding.definition_id)
+        .filter(|vault| vault.holding_id == vault_id)
+        .ok_or(TreasuryError::UnknownVault)?
+        .require_active()?;
+    if clock_id != clock_account_id() {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    let now = read_clock(&clock_data.data).ok_or(TreasuryError::InvalidClock)?;
+
+    let mut schedule =
+        Schedule::try_from_slice(&schedule_data.data).map_err(|_| TreasuryError::InvalidSchedule)?;
+    if schedule.recipient != recipient_id {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+
+    // Pay every installment due by now in one transfer
+    let due = schedule.due_count(now);
+    let pending = due.saturating_sub(schedule.paid);
+    if pending == 0 {
+        return Err(TreasuryError::NothingDue);
+    }
+    let amount = schedule
+        .amount
+        .checked_mul(u128::from(pending))
+        .ok_or(TreasuryError::Overflow)?;
+    schedule.paid = due;
+    accounts[2].account.data = account_data::encode(&schedule)?;
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::ExecuteDue,
+        vault_id,
+        recipient_id,
+        amount,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // Pay out from the vault, authorized by the treasury through its PDA seed
+    let payout_call = pda_transfer_call(
+        token_program_id,
+        &accounts[1],
+        &accounts[3],
+        vault_holding_pda_seed(&state.treasury_id, &vault_holding.definition_id),
+        amount,
+    );
+
+    // Build post_states
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let vault_post = AccountPostState::new(vault_data);
+    let schedule_post = AccountPostState::new(accounts[2].account.clone());
+    let recipient_post = AccountPostState::new(recipient_data);
+    let clock_post = AccountPostState::new(clock_data);
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![
+            treasury_post,
+            vault_post,
+            schedule_post,
+            recipient_post,
+            clock_post,
+            ledger_post,
+        ],
+        chained_calls: vec![payout_call],
+    })
+}
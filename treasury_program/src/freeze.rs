@@ -0,0 +1,65 @@
+//! Handlers for FreezeVault and UnfreezeVault — stop or resume fund movement
+//! through a single vault.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::AccountWithMetadata;
+use nssa_core::program::{AccountPostState, ProgramOutput};
+use treasury_core::{LedgerKind, Role, TokenHolding, TreasuryError, TreasuryState, VaultStatus};
+
+use crate::account_data;
+use crate::auth::require_admin_or_role;
+use crate::ledger;
+
+/// FreezeVault. Accounts: `[treasury_state, vault_holding, signer,
+/// ledger_page]`. The admin or a guardian must sign. Freezing a frozen vault
+/// is a no-op.
+pub fn freeze(accounts: &mut [AccountWithMetadata]) -> Result<ProgramOutput, TreasuryError> {
+    set_status(accounts, LedgerKind::FreezeVault, VaultStatus::Frozen)
+}
+
+/// UnfreezeVault. Accounts: `[treasury_state, vault_holding, signer,
+/// ledger_page]`. The admin or a guardian must sign. Unfreezing an active
+/// vault is a no-op.
+pub fn unfreeze(accounts: &mut [AccountWithMetadata]) -> Result<ProgramOutput, TreasuryError> {
+    set_status(accounts, LedgerKind::UnfreezeVault, VaultStatus::Active)
+}
+
+fn set_status(
+    accounts: &mut [AccountWithMetadata],
+    kind: LedgerKind,
+    status: VaultStatus,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 4 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 4,
+            actual: accounts.len(),
+        });
+    }
+
+    let mut state = TreasuryState::try_from_slice(&accounts[0].account.data).unwrap_or_default();
+    require_admin_or_role(&state, Role::Guardian, &accounts[2])?;
+
+    let vault_id = accounts[1].account_id;
+    let signer_id = accounts[2].account_id;
+    let vault_holding = TokenHolding::decode(&accounts[1].account.data)
+        .ok_or(TreasuryError::InvalidVaultHolding)?;
+    let vault = state
+        .vault_mut(&vault_holding.definition_id)
+        .filter(|vault| vault.holding_id == vault_id)
+        .ok_or(TreasuryError::UnknownVault)?;
+    vault.status = status;
+    let ledger_post = ledger::append(&mut state, accounts, kind, vault_id, signer_id, 0)?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // Build post_states
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let vault_post = AccountPostState::new(accounts[1].account.clone());
+    let signer_post = AccountPostState::new(accounts[2].account.clone());
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![treasury_post, vault_post, signer_post, ledger_post],
+        chained_calls: vec![],
+    })
+}
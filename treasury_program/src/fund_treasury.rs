@@ -0,0 +1,237 @@
+//! Handlers for FundTreasury and RedeemFromTreasury — pay from a vault into
+//! another treasury deployment through that treasury's Deposit, and redeem the
+//! shares it minted through its Redeem.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::{AccountId, AccountWithMetadata};
+use nssa_core::program::{AccountPostState, ChainedCall, PdaSeed, ProgramId, ProgramOutput};
+use treasury_core::{
+    Instruction, LedgerKind, TokenHolding, TreasuryError, TreasuryId, TreasuryState,
+    compute_share_definition_pda, compute_treasury_state_pda, compute_vault_holding_pda,
+    vault_holding_pda_seed,
+};
+
+use crate::account_data;
+use crate::auth::{require_admin, require_pda};
+use crate::ledger;
+
+/// Accounts: `[treasury_state, vault_holding, target_state, target_vault,
+/// target_share_definition, share_holding, admin, target_ledger_page,
+/// ledger_page]`. The admin must sign.
+///
+/// The target accounts must be the target treasury's PDAs for the token, and
+/// `share_holding` this treasury's vault PDA of the target's share
+/// definition. `target_ledger_page` is passed through to the target's
+/// Deposit, which checks it.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    target_program_id: &ProgramId,
+    target_treasury: &TreasuryId,
+    token_definition_id: &AccountId,
+    amount: u128,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 9 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 9,
+            actual: accounts.len(),
+        });
+    }
+    if amount == 0 {
+        return Err(TreasuryError::ZeroAmount);
+    }
+
+    let mut state = TreasuryState::try_from_slice(&accounts[0].account.data).unwrap_or_default();
+    let share_seed =
+        check_accounts(&state, accounts, target_program_id, target_treasury, token_definition_id)?;
+
+    let vault_id = accounts[1].account_id;
+    let target_vault_id = accounts[3].account_id;
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::FundTreasury,
+        vault_id,
+        target_vault_id,
+        amount,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // The vault pays as the depositor, authorized through its PDA seed. The
+    // share holding is authorized too, so the Token program can claim it on
+    // the first payment to the target.
+    let vault_meta = AccountWithMetadata::new(accounts[1].account.clone(), true, vault_id);
+    let share_holding_meta =
+        AccountWithMetadata::new(accounts[5].account.clone(), true, accounts[5].account_id);
+    let deposit = Instruction::Deposit {
+        amount,
+        token_program_id: *token_program_id,
+        memo: None,
+        private_sender: false,
+    };
+    let deposit_call = ChainedCall::new(
+        *target_program_id,
+        vec![
+            accounts[2].clone(),
+            vault_meta,
+            accounts[3].clone(),
+            accounts[4].clone(),
+            share_holding_meta,
+            accounts[7].clone(),
+        ],
+        &deposit,
+    )
+    .with_pda_seeds(vec![
+        vault_holding_pda_seed(&state.treasury_id, token_definition_id),
+        share_seed,
+    ]);
+
+    // Build post_states; the target treasury and the Token program move the
+    // funds
+    let mut post_states: Vec<_> = accounts
+        .iter()
+        .map(|account| AccountPostState::new(account.account.clone()))
+        .collect();
+    *post_states.last_mut().unwrap() = ledger_post;
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states,
+        chained_calls: vec![deposit_call],
+    })
+}
+
+/// Accounts: `[treasury_state, vault_holding, target_state, target_vault,
+/// target_share_definition, share_holding, admin, target_ledger_page,
+/// ledger_page]`, as for FundTreasury. The admin must sign.
+///
+/// `shares` are burned from `share_holding`, authorized through its PDA seed,
+/// and the target pays the proportional amount into `vault_holding`. The
+/// ledger entry records the shares redeemed: the payout is the target's to
+/// work out.
+pub fn redeem(
+    accounts: &mut [AccountWithMetadata],
+    target_program_id: &ProgramId,
+    target_treasury: &TreasuryId,
+    token_definition_id: &AccountId,
+    shares: u128,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 9 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 9,
+            actual: accounts.len(),
+        });
+    }
+    if shares == 0 {
+        return Err(TreasuryError::InvalidShareAmount);
+    }
+
+    let mut state = TreasuryState::try_from_slice(&accounts[0].account.data).unwrap_or_default();
+    let share_seed =
+        check_accounts(&state, accounts, target_program_id, target_treasury, token_definition_id)?;
+
+    let vault_id = accounts[1].account_id;
+    let target_vault_id = accounts[3].account_id;
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::RedeemFromTreasury,
+        vault_id,
+        target_vault_id,
+        shares,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // The share holding is burned from, authorized through its PDA seed; the
+    // vault only receives the payout
+    let share_holding_meta =
+        AccountWithMetadata::new(accounts[5].account.clone(), true, accounts[5].account_id);
+    let redeem = Instruction::Redeem {
+        shares,
+        token_program_id: *token_program_id,
+    };
+    let redeem_call = ChainedCall::new(
+        *target_program_id,
+        vec![
+            accounts[2].clone(),
+            accounts[3].clone(),
+            accounts[4].clone(),
+            share_holding_meta,
+            accounts[1].clone(),
+            accounts[7].clone(),
+        ],
+        &redeem,
+    )
+    .with_pda_seeds(vec![share_seed]);
+
+    // Build post_states; the target treasury and the Token program move the
+    // funds
+    let mut post_states: Vec<_> = accounts
+        .iter()
+        .map(|account| AccountPostState::new(account.account.clone()))
+        .collect();
+    *post_states.last_mut().unwrap() = ledger_post;
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states,
+        chained_calls: vec![redeem_call],
+    })
+}
+
+/// Check the accounts both handlers share: the admin signed, the vault is
+/// this treasury's active vault of the token, and the target accounts are the
+/// target treasury's PDAs for it. Returns the seed of `share_holding`, this
+/// treasury's vault PDA of the target's share definition.
+fn check_accounts(
+    state: &TreasuryState,
+    accounts: &[AccountWithMetadata],
+    target_program_id: &ProgramId,
+    target_treasury: &TreasuryId,
+    token_definition_id: &AccountId,
+) -> Result<PdaSeed, TreasuryError> {
+    require_admin(state, &accounts[6])?;
+    // Paying this treasury is a plain Deposit, and redeeming from it a plain
+    // Redeem
+    if *target_program_id == accounts[0].account.program_owner
+        && *target_treasury == state.treasury_id
+    {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+
+    let vault_id = accounts[1].account_id;
+    let vault_holding = TokenHolding::decode(&accounts[1].account.data)
+        .ok_or(TreasuryError::InvalidVaultHolding)?;
+    if vault_holding.definition_id != *token_definition_id {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    state
+        .vault(token_definition_id)
+        .filter(|vault| vault.holding_id == vault_id)
+        .ok_or(TreasuryError::UnknownVault)?
+        .require_active()?;
+
+    // The other side must be the target treasury's PDAs for the token
+    let target_share_def_id =
+        compute_share_definition_pda(target_program_id, target_treasury, token_definition_id);
+    let expected = [
+        (2, compute_treasury_state_pda(target_program_id, target_treasury)),
+        (
+            3,
+            compute_vault_holding_pda(target_program_id, target_treasury, token_definition_id),
+        ),
+        (4, target_share_def_id),
+    ];
+    if expected
+        .iter()
+        .any(|(index, account_id)| accounts[*index].account_id != *account_id)
+    {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    let share_seed = vault_holding_pda_seed(&state.treasury_id, &target_share_def_id);
+    require_pda(&accounts[5].account_id, &accounts[0], &share_seed)?;
+    Ok(share_seed)
+}
@@ -0,0 +1,145 @@
+//! Handlers for ApproveMilestone and CancelGrant — pay out or wind down a
+//! milestone grant.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::AccountWithMetadata;
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use treasury_core::{
+    Grant, LedgerKind, MilestoneState, Role, TreasuryError, TreasuryState,
+    compute_treasury_state_pda, grant_holding_pda_seed, grant_pda_seed,
+};
+
+use crate::account_data;
+use crate::auth::{require_admin, require_pda, require_role};
+use crate::ledger;
+use crate::token::pda_transfer_call;
+
+/// ApproveMilestone. Accounts: `[treasury_state, grant, grant_holding,
+/// grantee_holding, reviewer, ledger_page]`. The reviewer must sign.
+pub fn approve(
+    accounts: &mut [AccountWithMetadata],
+    grant: u64,
+    milestone: u32,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    let (state, mut record) = load(accounts, grant)?;
+    require_role(&state, Role::Reviewer, &accounts[4])?;
+    if accounts[3].account_id != record.grantee {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+
+    // Milestones are released strictly in order
+    let next = record.next_pending().ok_or(TreasuryError::GrantClosed)?;
+    if usize::try_from(milestone).ok() != Some(next) {
+        return Err(TreasuryError::InvalidMilestone);
+    }
+    record.milestones[next].state = MilestoneState::Released;
+    let amount = record.milestones[next].amount;
+
+    let payout = (LedgerKind::ApproveMilestone, amount);
+    pay_out(accounts, state, grant, &record, payout, token_program_id)
+}
+
+/// CancelGrant. Accounts: `[treasury_state, grant, grant_holding,
+/// vault_holding, admin, ledger_page]`. The admin must sign.
+pub fn cancel(
+    accounts: &mut [AccountWithMetadata],
+    grant: u64,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    let (state, mut record) = load(accounts, grant)?;
+    require_admin(&state, &accounts[4])?;
+    if accounts[3].account_id != record.vault_id {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    // The unreleased funds return to the vault
+    state
+        .vault(&record.token_definition_id)
+        .ok_or(TreasuryError::UnknownVault)?
+        .require_active()?;
+
+    if record.next_pending().is_none() {
+        return Err(TreasuryError::GrantClosed);
+    }
+    let unreleased = record
+        .total_in(MilestoneState::Pending)
+        .ok_or(TreasuryError::Overflow)?;
+    for milestone in &mut record.milestones {
+        if milestone.state == MilestoneState::Pending {
+            milestone.state = MilestoneState::Cancelled;
+        }
+    }
+
+    let payout = (LedgerKind::CancelGrant, unreleased);
+    pay_out(accounts, state, grant, &record, payout, token_program_id)
+}
+
+/// Check the account count and decode the treasury state and the grant.
+fn load(
+    accounts: &[AccountWithMetadata],
+    grant: u64,
+) -> Result<(TreasuryState, Grant), TreasuryError> {
+    if accounts.len() != 6 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 6,
+            actual: accounts.len(),
+        });
+    }
+
+    // The grant is written, so its owner is this program; use it to check
+    // the treasury state is ours too.
+    let state = TreasuryState::try_from_slice(&accounts[0].account.data).unwrap_or_default();
+    let treasury = &state.treasury_id;
+    require_pda(&accounts[1].account_id, &accounts[1], &grant_pda_seed(treasury, grant))?;
+    let owner = &accounts[1].account.program_owner;
+    if accounts[0].account_id != compute_treasury_state_pda(owner, treasury) {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    let record = Grant::decode(&accounts[1].account.data).ok_or(TreasuryError::UnexpectedAccount)?;
+    Ok((state, record))
+}
+
+/// Store the updated grant, record the payout in the ledger and pay its
+/// amount from the grant holding to `accounts[3]`.
+fn pay_out(
+    accounts: &mut [AccountWithMetadata],
+    mut state: TreasuryState,
+    grant: u64,
+    record: &Grant,
+    (kind, amount): (LedgerKind, u128),
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    accounts[1].account.data = account_data::encode(record)?;
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        kind,
+        record.vault_id,
+        record.grantee,
+        amount,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // Pay out from the grant holding, authorized through its PDA seed
+    let payout_call = pda_transfer_call(
+        token_program_id,
+        &accounts[2],
+        &accounts[3],
+        grant_holding_pda_seed(&state.treasury_id, grant),
+        amount,
+    );
+
+    // Build post_states; the ledger page may be claimed
+    let mut post_states: Vec<_> = accounts
+        .iter()
+        .map(|account| AccountPostState::new(account.account.clone()))
+        .collect();
+    *post_states.last_mut().unwrap() = ledger_post;
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states,
+        chained_calls: vec![payout_call],
+    })
+}
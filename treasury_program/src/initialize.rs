@@ -0,0 +1,73 @@
+//! Handler for Initialize — creates the treasury state with its admin.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::{Account, AccountId, AccountWithMetadata};
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use treasury_core::{
+    LedgerKind, TreasuryError, TreasuryId, TreasuryState, compute_ledger_page_pda,
+    compute_treasury_state_pda,
+};
+
+use crate::account_data;
+use crate::ledger;
+
+/// Accounts: `[treasury_state, admin, ledger_page]`. The admin must sign and
+/// becomes the creator the treasury ID is derived from.
+///
+/// Fails on a treasury that already exists. The state and the first ledger
+/// page must be the PDAs of that ID under `treasury_program_id`: the state
+/// is not claimed yet, so its owner cannot vouch for the program ID.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    name: &str,
+    treasury_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 3 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 3,
+            actual: accounts.len(),
+        });
+    }
+    if !accounts[1].is_authorized {
+        return Err(TreasuryError::Unauthorized);
+    }
+
+    // Read data first to avoid borrow issues
+    let treasury_is_new = accounts[0].account == Account::default();
+    let treasury_data = accounts[0].account.data.clone();
+    let admin_data = accounts[1].account.clone();
+    let admin_id = accounts[1].account_id;
+
+    if !treasury_is_new {
+        return Err(TreasuryError::AlreadyInitialized);
+    }
+    let treasury = TreasuryId::from_name(&admin_id, name);
+    if accounts[0].account_id != compute_treasury_state_pda(treasury_program_id, &treasury)
+        || accounts[2].account_id != compute_ledger_page_pda(treasury_program_id, &treasury, 0)
+    {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    state.treasury_id = treasury;
+    state.admin = Some(admin_id);
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::Initialize,
+        AccountId::default(),
+        admin_id,
+        0,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // Build post_states. The treasury claims its state.
+    let treasury_post = AccountPostState::new_claimed(accounts[0].account.clone());
+    let admin_post = AccountPostState::new(admin_data);
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![treasury_post, admin_post, ledger_post],
+        chained_calls: vec![],
+    })
+}
@@ -0,0 +1,99 @@
+//! Handler for InternalTransfer — moves tokens between two vaults of the same
+//! token.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::AccountWithMetadata;
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use treasury_core::{LedgerKind, TokenHolding, TreasuryError, TreasuryState, vault_pda_seed};
+
+use crate::account_data;
+use crate::auth::{require_admin, require_pda};
+use crate::ledger;
+use crate::token;
+
+/// Accounts: `[treasury_state, from_vault, to_vault, admin, ledger_page]`.
+/// The admin must sign.
+///
+/// Both vaults must be token holdings of the same definition and PDAs of this
+/// treasury for that token, and the token must be registered. The vaults are
+/// named by label rather than account ID, since the label is what derives
+/// their PDA seeds.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    from_label: Option<&str>,
+    to_label: Option<&str>,
+    amount: u128,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 5 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 5,
+            actual: accounts.len(),
+        });
+    }
+    if amount == 0 {
+        return Err(TreasuryError::ZeroAmount);
+    }
+    if from_label == to_label {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+
+    // Read data first to avoid borrow issues
+    let treasury_data = accounts[0].account.data.clone();
+    let from_data = accounts[1].account.clone();
+    let to_data = accounts[2].account.clone();
+    let from_id = accounts[1].account_id;
+    let to_id = accounts[2].account_id;
+
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    require_admin(&state, &accounts[3])?;
+
+    // Both vaults must hold the same token
+    let from_holding =
+        TokenHolding::decode(&from_data.data).ok_or(TreasuryError::InvalidVaultHolding)?;
+    let to_holding =
+        TokenHolding::decode(&to_data.data).ok_or(TreasuryError::InvalidVaultHolding)?;
+    if to_holding.definition_id != from_holding.definition_id {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    let definition_id = from_holding.definition_id;
+    state
+        .vault(&definition_id)
+        .ok_or(TreasuryError::UnknownVault)?
+        .require_active()?;
+
+    // Both vaults must be this treasury's PDAs for the token
+    let from_seed = vault_pda_seed(&state.treasury_id, &definition_id, from_label);
+    let to_seed = vault_pda_seed(&state.treasury_id, &definition_id, to_label);
+    require_pda(&from_id, &accounts[0], &from_seed)?;
+    require_pda(&to_id, &accounts[0], &to_seed)?;
+
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::InternalTransfer,
+        from_id,
+        to_id,
+        amount,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // The paying vault authorizes the transfer through its PDA seed
+    let from_meta = AccountWithMetadata::new(from_data.clone(), true, from_id);
+    let to_meta = AccountWithMetadata::new(to_data.clone(), false, to_id);
+    let transfer_call = token::transfer_call(token_program_id, vec![from_meta, to_meta], amount)
+        .with_pda_seeds(vec![from_seed]);
+
+    // Build post_states
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let from_post = AccountPostState::new(from_data);
+    let to_post = AccountPostState::new(to_data);
+    let admin_post = AccountPostState::new(accounts[3].account.clone());
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![treasury_post, from_post, to_post, admin_post, ledger_post],
+        chained_calls: vec![transfer_call],
+    })
+}
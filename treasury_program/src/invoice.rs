@@ -0,0 +1,212 @@
+//! Handlers for CreateInvoice and PayInvoice — request a payment into a vault
+//! and settle it exactly once.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::{Account, AccountId, AccountWithMetadata};
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use treasury_core::{
+    Invoice, InvoiceStatus, LedgerKind, TokenHolding, TreasuryError, TreasuryState,
+    clock_account_id, compute_treasury_state_pda, invoice_pda_seed, read_clock, validate_memo,
+};
+
+use crate::account_data;
+use crate::auth::{require_admin, require_pda};
+use crate::ledger;
+use crate::token;
+
+/// CreateInvoice. Accounts: `[treasury_state, vault_holding, invoice, admin,
+/// ledger_page]`. The invoice must be the PDA for the next invoice ID; the
+/// admin must sign.
+pub fn create(
+    accounts: &mut [AccountWithMetadata],
+    amount: u128,
+    payer: Option<AccountId>,
+    due: u64,
+    reference: &str,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 5 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 5,
+            actual: accounts.len(),
+        });
+    }
+
+    // Read data first to avoid borrow issues
+    let treasury_data = accounts[0].account.data.clone();
+    let vault_data = accounts[1].account.clone();
+    let invoice_data = accounts[2].account.clone();
+    let admin_data = accounts[3].account.clone();
+    let vault_id = accounts[1].account_id;
+    let invoice_id = accounts[2].account_id;
+
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    require_admin(&state, &accounts[3])?;
+
+    let vault_holding =
+        TokenHolding::decode(&vault_data.data).ok_or(TreasuryError::InvalidVaultHolding)?;
+    state
+        .vault(&vault_holding.definition_id)
+        .filter(|vault| vault.holding_id == vault_id)
+        .ok_or(TreasuryError::UnknownVault)?;
+    if amount == 0 {
+        return Err(TreasuryError::ZeroAmount);
+    }
+    validate_memo(Some(reference))?;
+
+    // Invoice IDs are treasury-wide
+    let index = state.invoice_count;
+    require_pda(&invoice_id, &accounts[0], &invoice_pda_seed(&state.treasury_id, index))?;
+    if invoice_data != Account::default() {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    state.invoice_count = index.checked_add(1).ok_or(TreasuryError::Overflow)?;
+    let ledger_post = ledger::append_with_memo(
+        &mut state,
+        accounts,
+        LedgerKind::CreateInvoice,
+        vault_id,
+        payer.unwrap_or_default(),
+        amount,
+        Some(reference),
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    let invoice = Invoice {
+        token_definition_id: vault_holding.definition_id,
+        vault_id,
+        amount,
+        payer,
+        due,
+        reference: reference.to_string(),
+        status: InvoiceStatus::Open,
+        paid_by: None,
+    };
+    let mut invoice_account = invoice_data;
+    invoice_account.data = account_data::encode(&invoice)?;
+
+    // Build post_states. The treasury claims the invoice PDA.
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let vault_post = AccountPostState::new(vault_data);
+    let invoice_post = AccountPostState::new_claimed(invoice_account);
+    let admin_post = AccountPostState::new(admin_data);
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![treasury_post, vault_post, invoice_post, admin_post, ledger_post],
+        chained_calls: vec![],
+    })
+}
+
+/// PayInvoice. Accounts: `[treasury_state, invoice, payer_holding,
+/// vault_holding, clock, ledger_page]`. The payer must sign, and must be the
+/// invoice's payer if it names one. The invoice must not be past its due
+/// time.
+pub fn pay(
+    accounts: &mut [AccountWithMetadata],
+    invoice: u64,
+    amount: u128,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 6 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 6,
+            actual: accounts.len(),
+        });
+    }
+
+    // Read data first to avoid borrow issues
+    let treasury_data = accounts[0].account.data.clone();
+    let invoice_data = accounts[1].account.clone();
+    let payer_data = accounts[2].account.clone();
+    let vault_data = accounts[3].account.clone();
+    let invoice_id = accounts[1].account_id;
+    let payer_id = accounts[2].account_id;
+    let payer_authorized = accounts[2].is_authorized;
+    let vault_id = accounts[3].account_id;
+    let clock_data = accounts[4].account.clone();
+    let clock_id = accounts[4].account_id;
+
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    require_pda(
+        &invoice_id,
+        &accounts[1],
+        &invoice_pda_seed(&state.treasury_id, invoice),
+    )?;
+    let mut record = Invoice::try_from_slice(&invoice_data.data)
+        .map_err(|_| TreasuryError::UnexpectedAccount)?;
+    if record.status != InvoiceStatus::Open {
+        return Err(TreasuryError::InvoicePaid);
+    }
+
+    // The invoice is written, so its owner is this program; use it to check
+    // the treasury state is ours too.
+    let owner = &invoice_data.program_owner;
+    if accounts[0].account_id != compute_treasury_state_pda(owner, &state.treasury_id) {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    if vault_id != record.vault_id {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    if !payer_authorized || record.payer.is_some_and(|payer| payer != payer_id) {
+        return Err(TreasuryError::Unauthorized);
+    }
+    if amount != record.amount {
+        return Err(TreasuryError::WrongPaymentAmount {
+            due: record.amount,
+            paid: amount,
+        });
+    }
+    if clock_id != clock_account_id() {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    let now = read_clock(&clock_data.data).ok_or(TreasuryError::InvalidClock)?;
+    if now > record.due {
+        return Err(TreasuryError::InvoiceOverdue);
+    }
+    state
+        .vault(&record.token_definition_id)
+        .ok_or(TreasuryError::UnknownVault)?
+        .require_active()?;
+
+    record.status = InvoiceStatus::Paid;
+    record.paid_by = Some(payer_id);
+    accounts[1].account.data = account_data::encode(&record)?;
+    let ledger_post = ledger::append_with_memo(
+        &mut state,
+        accounts,
+        LedgerKind::PayInvoice,
+        vault_id,
+        payer_id,
+        amount,
+        Some(&record.reference),
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // Payer authorizes the transfer, vault receives
+    let payer_meta = AccountWithMetadata::new(payer_data.clone(), true, payer_id);
+    let vault_meta = AccountWithMetadata::new(vault_data.clone(), false, vault_id);
+    let transfer_call =
+        token::transfer_call(token_program_id, vec![payer_meta, vault_meta], amount);
+
+    // Build post_states
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let invoice_post = AccountPostState::new(accounts[1].account.clone());
+    let payer_post = AccountPostState::new(payer_data);
+    let vault_post = AccountPostState::new(vault_data);
+    let clock_post = AccountPostState::new(clock_data);
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![
+            treasury_post,
+            invoice_post,
+            payer_post,
+            vault_post,
+            clock_post,
+            ledger_post,
+        ],
+        chained_calls: vec![transfer_call],
+    })
+}
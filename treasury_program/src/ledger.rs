@@ -0,0 +1,81 @@
+//! Ledger appends shared by the handlers.
+
+use nssa_core::account::{Account, AccountId, AccountWithMetadata};
+use nssa_core::program::{AccountPostState, ProgramId};
+use treasury_core::{
+    LEDGER_PAGE_ENTRIES, LedgerEntry, LedgerKind, TreasuryError, TreasuryState, ledger_page_len,
+    ledger_page_pda_seed, treasury_state_pda_seed, validate_memo,
+};
+
+use crate::auth::require_pda;
+
+/// Append an entry to the ledger and advance `state.ledger_len`. Returns the
+/// ledger page's post state, claimed when the page gets its first entry.
+///
+/// `accounts[0]` must be the treasury state, which the handler writes, and the
+/// last account the ledger page the entry falls on. Both are checked to be
+/// PDAs of the state's treasury under the state's owner (see
+/// [`require_pda`]), except on Initialize, when the owner is not yet known
+/// and the handler checks them itself.
+pub fn append(
+    state: &mut TreasuryState,
+    accounts: &mut [AccountWithMetadata],
+    kind: LedgerKind,
+    vault: AccountId,
+    counterparty: AccountId,
+    amount: u128,
+) -> Result<AccountPostState, TreasuryError> {
+    append_with_memo(state, accounts, kind, vault, counterparty, amount, None)
+}
+
+/// [`append`] an entry carrying a Send or Deposit memo, after checking it
+/// with [`validate_memo`].
+pub fn append_with_memo(
+    state: &mut TreasuryState,
+    accounts: &mut [AccountWithMetadata],
+    kind: LedgerKind,
+    vault: AccountId,
+    counterparty: AccountId,
+    amount: u128,
+    memo: Option<&str>,
+) -> Result<AccountPostState, TreasuryError> {
+    validate_memo(memo)?;
+    let seq = state.ledger_len;
+    let last = accounts
+        .len()
+        .checked_sub(1)
+        .ok_or(TreasuryError::InvalidAccountCount {
+            expected: 1,
+            actual: 0,
+        })?;
+    if accounts[0].account.program_owner != ProgramId::default() {
+        let state_seed = treasury_state_pda_seed(&state.treasury_id);
+        require_pda(&accounts[0].account_id, &accounts[0], &state_seed)?;
+        let seed = ledger_page_pda_seed(&state.treasury_id, seq / LEDGER_PAGE_ENTRIES);
+        require_pda(&accounts[last].account_id, &accounts[0], &seed)?;
+    }
+
+    let page = &mut accounts[last].account;
+    let page_is_new = *page == Account::default();
+    if ledger_page_len(&page.data).is_none_or(|len| len >= LEDGER_PAGE_ENTRIES) {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    let entry = LedgerEntry {
+        kind,
+        vault,
+        counterparty,
+        amount,
+        seq,
+        memo: memo.map(str::to_owned),
+    };
+    let mut data = page.data.to_vec();
+    data.extend(entry.encode());
+    page.data = data.try_into().map_err(|_| TreasuryError::AccountDataTooLarge)?;
+    state.ledger_len = seq.checked_add(1).ok_or(TreasuryError::Overflow)?;
+
+    Ok(if page_is_new {
+        AccountPostState::new_claimed(page.clone())
+    } else {
+        AccountPostState::new(page.clone())
+    })
+}
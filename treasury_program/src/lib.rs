@@ -3,24 +3,219 @@
 pub mod create_vault;
 pub mod send;
 pub mod deposit;
+pub mod redeem;
+pub mod initialize;
+pub mod create_schedule;
+pub mod execute_due;
+pub mod create_escrow;
+pub mod settle_escrow;
+pub mod roles;
+pub mod create_grant;
+pub mod grant_milestones;
+pub mod mint_more;
+pub mod set_supply_cap;
+pub mod burn;
+pub mod set_amm_program;
+pub mod swap;
+pub mod liquidity;
+pub mod invoice;
+pub mod split;
+pub mod internal_transfer;
+pub mod fund_treasury;
+pub mod freeze;
+pub mod set_metadata;
+mod account_data;
+mod amm;
+mod auth;
+mod ledger;
+mod token;
 
-pub use treasury_core::Instruction;
+pub use treasury_core::{Instruction, TreasuryError};
 
 use nssa_core::account::AccountWithMetadata;
 use nssa_core::program::ProgramOutput;
 
 /// Dispatch incoming instructions to their handlers.
+///
+/// Returns an error if the handler rejects the instruction; the guest turns
+/// that into a failed execution, so nothing is committed.
 pub fn process(
     accounts: &mut [AccountWithMetadata],
     instruction: &Instruction,
-) -> ProgramOutput {
+) -> Result<ProgramOutput, TreasuryError> {
     match instruction {
         Instruction::CreateVault {
             token_name,
+            metadata,
             initial_supply,
             token_program_id,
-        } => create_vault::handle(accounts, token_name, *initial_supply, token_program_id),
-        Instruction::Send { amount, token_program_id } => send::handle(accounts, *amount, token_program_id),
-        Instruction::Deposit { amount, token_program_id } => deposit::handle(accounts, *amount, token_program_id),
+        } => create_vault::handle(
+            accounts,
+            token_name,
+            metadata,
+            *initial_supply,
+            token_program_id,
+        ),
+        Instruction::Send {
+            amount,
+            token_program_id,
+            memo,
+            private_recipient,
+        } => send::handle(
+            accounts,
+            *amount,
+            token_program_id,
+            memo.as_deref(),
+            *private_recipient,
+        ),
+        Instruction::Deposit {
+            amount,
+            token_program_id,
+            memo,
+            private_sender,
+        } => deposit::handle(
+            accounts,
+            *amount,
+            token_program_id,
+            memo.as_deref(),
+            *private_sender,
+        ),
+        Instruction::Redeem { shares, token_program_id } => redeem::handle(accounts, *shares, token_program_id),
+        Instruction::Initialize {
+            name,
+            treasury_program_id,
+        } => initialize::handle(accounts, name, treasury_program_id),
+        Instruction::CreateSchedule {
+            recipient,
+            amount,
+            interval,
+            start,
+            count,
+        } => create_schedule::handle(accounts, recipient, *amount, *interval, *start, *count),
+        Instruction::ExecuteDue { schedule, token_program_id } => {
+            execute_due::handle(accounts, *schedule, token_program_id)
+        }
+        Instruction::CreateEscrow {
+            payer_vault,
+            beneficiary,
+            amount,
+            arbiter,
+            deadline,
+            token_program_id,
+        } => create_escrow::handle(
+            accounts,
+            payer_vault,
+            beneficiary,
+            *amount,
+            arbiter,
+            *deadline,
+            token_program_id,
+        ),
+        Instruction::Release { escrow, token_program_id } => {
+            settle_escrow::release(accounts, *escrow, token_program_id)
+        }
+        Instruction::Refund { escrow, token_program_id } => {
+            settle_escrow::refund(accounts, *escrow, token_program_id)
+        }
+        Instruction::Reclaim { escrow, token_program_id } => {
+            settle_escrow::reclaim(accounts, *escrow, token_program_id)
+        }
+        Instruction::AssignRole { role, account } => roles::assign(accounts, *role, account),
+        Instruction::RevokeRole { role, account } => roles::revoke(accounts, *role, account),
+        Instruction::CreateGrant {
+            grantee,
+            milestones,
+            token_program_id,
+        } => create_grant::handle(accounts, grantee, milestones, token_program_id),
+        Instruction::ApproveMilestone {
+            grant,
+            milestone,
+            token_program_id,
+        } => grant_milestones::approve(accounts, *grant, *milestone, token_program_id),
+        Instruction::CancelGrant { grant, token_program_id } => {
+            grant_milestones::cancel(accounts, *grant, token_program_id)
+        }
+        Instruction::MintMore { amount, token_program_id } => {
+            mint_more::handle(accounts, *amount, token_program_id)
+        }
+        Instruction::SetSupplyCap { cap } => set_supply_cap::handle(accounts, *cap),
+        Instruction::Burn { amount, token_program_id } => burn::handle(accounts, *amount, token_program_id),
+        Instruction::SetAmmProgram { amm_program_id } => {
+            set_amm_program::handle(accounts, amm_program_id)
+        }
+        Instruction::Swap {
+            amount_in,
+            min_amount_out,
+        } => swap::handle(accounts, *amount_in, *min_amount_out),
+        Instruction::AddLiquidity {
+            max_amount_a,
+            max_amount_b,
+            min_amount_lp,
+        } => liquidity::add(accounts, *max_amount_a, *max_amount_b, *min_amount_lp),
+        Instruction::RemoveLiquidity {
+            lp_amount,
+            min_amount_a,
+            min_amount_b,
+        } => liquidity::remove(accounts, *lp_amount, *min_amount_a, *min_amount_b),
+        Instruction::SetSplit { split } => split::set(accounts, split.as_ref()),
+        Instruction::DepositSplit { amount, token_program_id } => {
+            split::deposit(accounts, *amount, token_program_id)
+        }
+        Instruction::CreateInvoice {
+            amount,
+            payer,
+            due,
+            reference,
+        } => invoice::create(accounts, *amount, *payer, *due, reference),
+        Instruction::PayInvoice {
+            invoice,
+            amount,
+            token_program_id,
+        } => invoice::pay(accounts, *invoice, *amount, token_program_id),
+        Instruction::InternalTransfer {
+            from_vault,
+            to_vault,
+            amount,
+            token_program_id,
+        } => internal_transfer::handle(
+            accounts,
+            from_vault.as_deref(),
+            to_vault.as_deref(),
+            *amount,
+            token_program_id,
+        ),
+        Instruction::FundTreasury {
+            target_treasury_program_id,
+            target_treasury,
+            token_definition,
+            amount,
+            token_program_id,
+        } => fund_treasury::handle(
+            accounts,
+            target_treasury_program_id,
+            target_treasury,
+            token_definition,
+            *amount,
+            token_program_id,
+        ),
+        Instruction::RedeemFromTreasury {
+            target_treasury_program_id,
+            target_treasury,
+            token_definition,
+            shares,
+            token_program_id,
+        } => fund_treasury::redeem(
+            accounts,
+            target_treasury_program_id,
+            target_treasury,
+            token_definition,
+            *shares,
+            token_program_id,
+        ),
+        Instruction::FreezeVault => freeze::freeze(accounts),
+        Instruction::UnfreezeVault => freeze::unfreeze(accounts),
+        Instruction::SetMetadata { name, uri, tags } => {
+            set_metadata::handle(accounts, name, uri, tags)
+        }
     }
 }
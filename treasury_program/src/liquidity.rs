@@ -0,0 +1,212 @@
+//! Handlers for AddLiquidity and RemoveLiquidity — provide AMM liquidity from
+//! two treasury vaults, holding the LP tokens in a treasury LP vault.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::{AccountId, AccountWithMetadata};
+use nssa_core::program::{AccountPostState, ChainedCall, ProgramId, ProgramOutput};
+use treasury_core::{
+    LedgerKind, TokenHolding, TreasuryError, TreasuryState, VaultRecord, vault_holding_pda_seed,
+};
+
+use crate::account_data;
+use crate::amm;
+use crate::auth::{require_admin, require_state_pda};
+use crate::ledger;
+
+/// AddLiquidity. Accounts: `[treasury_state, vault_a, vault_b, lp_vault,
+/// pool, pool_vault_a, pool_vault_b, pool_lp_definition, admin,
+/// ledger_page]`. The admin must sign, and the pool must belong to the AMM
+/// program set with SetAmmProgram. The LP vault is registered on first use.
+pub fn add(
+    accounts: &mut [AccountWithMetadata],
+    max_amount_a: u128,
+    max_amount_b: u128,
+    min_amount_lp: u128,
+) -> Result<ProgramOutput, TreasuryError> {
+    let pool = check_pool(accounts)?;
+    if max_amount_a == 0 || max_amount_b == 0 {
+        return Err(TreasuryError::ZeroAmount);
+    }
+    if min_amount_lp == 0 {
+        return Err(TreasuryError::InvalidSlippage);
+    }
+
+    // The LP vault is a vault like any other, keyed by the LP token definition
+    let mut state = pool.state;
+    match state.vault(&pool.lp_def) {
+        Some(lp_vault) => lp_vault.require_active()?,
+        None => {
+            state.vaults.push(VaultRecord::new(pool.lp_def, accounts[3].account_id));
+            state.vault_count = state.vault_count.checked_add(1).ok_or(TreasuryError::Overflow)?;
+        }
+    }
+    let ledger_post = record(&mut state, accounts, LedgerKind::AddLiquidity, min_amount_lp)?;
+
+    // Both vaults pay in through their PDA seeds; the LP vault is authorized
+    // so it can be claimed on the first deposit
+    let call = amm::add_liquidity_call(
+        &pool.amm_program_id,
+        pool_accounts(accounts, [true, true, true]),
+        min_amount_lp,
+        max_amount_a,
+        max_amount_b,
+    )
+    .with_pda_seeds(vec![
+        vault_holding_pda_seed(&state.treasury_id, &pool.def_a),
+        vault_holding_pda_seed(&state.treasury_id, &pool.def_b),
+        vault_holding_pda_seed(&state.treasury_id, &pool.lp_def),
+    ]);
+    Ok(output(accounts, call, ledger_post))
+}
+
+/// RemoveLiquidity. Accounts as for AddLiquidity. The admin must sign.
+pub fn remove(
+    accounts: &mut [AccountWithMetadata],
+    lp_amount: u128,
+    min_amount_a: u128,
+    min_amount_b: u128,
+) -> Result<ProgramOutput, TreasuryError> {
+    let pool = check_pool(accounts)?;
+    if lp_amount == 0 {
+        return Err(TreasuryError::ZeroAmount);
+    }
+    if min_amount_a == 0 || min_amount_b == 0 {
+        return Err(TreasuryError::InvalidSlippage);
+    }
+    pool.state
+        .vault(&pool.lp_def)
+        .filter(|vault| vault.holding_id == accounts[3].account_id)
+        .ok_or(TreasuryError::UnknownVault)?
+        .require_active()?;
+    let mut state = pool.state;
+    let ledger_post = record(&mut state, accounts, LedgerKind::RemoveLiquidity, lp_amount)?;
+
+    // The LP vault pays in through its PDA seed; both vaults receive
+    let call = amm::remove_liquidity_call(
+        &pool.amm_program_id,
+        pool_accounts(accounts, [false, false, true]),
+        lp_amount,
+        min_amount_a,
+        min_amount_b,
+    )
+    .with_pda_seeds(vec![vault_holding_pda_seed(&state.treasury_id, &pool.lp_def)]);
+    Ok(output(accounts, call, ledger_post))
+}
+
+/// The pool's tokens, checked against the treasury's vaults.
+struct Pool {
+    state: TreasuryState,
+    amm_program_id: ProgramId,
+    def_a: AccountId,
+    def_b: AccountId,
+    lp_def: AccountId,
+}
+
+/// Check the accounts shared by both instructions: the admin, that the pool
+/// belongs to the pinned AMM program and records `pool_lp_definition` as its
+/// LP token, that the two treasury vaults hold the pool's tokens in pool
+/// order, and that the LP vault is the vault PDA of the LP token.
+fn check_pool(accounts: &[AccountWithMetadata]) -> Result<Pool, TreasuryError> {
+    if accounts.len() != 10 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 10,
+            actual: accounts.len(),
+        });
+    }
+
+    let state = TreasuryState::try_from_slice(&accounts[0].account.data).unwrap_or_default();
+    require_admin(&state, &accounts[8])?;
+    let amm_program_id = amm::pinned_program(&state, &accounts[4])?;
+
+    let def_a = TokenHolding::decode(&accounts[5].account.data)
+        .ok_or(TreasuryError::UnexpectedAccount)?
+        .definition_id;
+    let def_b = TokenHolding::decode(&accounts[6].account.data)
+        .ok_or(TreasuryError::UnexpectedAccount)?
+        .definition_id;
+    let lp_def = accounts[7].account_id;
+    if amm::pool_lp_definition(&accounts[4]) != Some(lp_def) {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    for (def, vault_id) in [(def_a, accounts[1].account_id), (def_b, accounts[2].account_id)] {
+        state
+            .vault(&def)
+            .filter(|vault| vault.holding_id == vault_id)
+            .ok_or(TreasuryError::UnknownVault)?
+            .require_active()?;
+    }
+
+    // The LP vault is authorized in every call, which proves the state's
+    // owner is this program
+    let lp_seed = vault_holding_pda_seed(&state.treasury_id, &lp_def);
+    require_state_pda(&accounts[0], &state.treasury_id, &accounts[3].account_id, &lp_seed)?;
+
+    Ok(Pool {
+        state,
+        amm_program_id,
+        def_a,
+        def_b,
+        lp_def,
+    })
+}
+
+/// Accounts of a chained AMM liquidity call, `[pool, pool_vault_a,
+/// pool_vault_b, pool_lp_definition, vault_a, vault_b, lp_vault]`, with the
+/// treasury vaults marked authorized per `authorized`.
+fn pool_accounts(
+    accounts: &[AccountWithMetadata],
+    authorized: [bool; 3],
+) -> Vec<AccountWithMetadata> {
+    let [a, b, lp] = authorized;
+    let treasury_meta = |index: usize, is_authorized: bool| {
+        AccountWithMetadata::new(
+            accounts[index].account.clone(),
+            is_authorized,
+            accounts[index].account_id,
+        )
+    };
+    vec![
+        accounts[4].clone(),
+        accounts[5].clone(),
+        accounts[6].clone(),
+        accounts[7].clone(),
+        treasury_meta(1, a),
+        treasury_meta(2, b),
+        treasury_meta(3, lp),
+    ]
+}
+
+/// Record the instruction in the ledger against the LP vault and the pool,
+/// and store the state.
+fn record(
+    state: &mut TreasuryState,
+    accounts: &mut [AccountWithMetadata],
+    kind: LedgerKind,
+    amount: u128,
+) -> Result<AccountPostState, TreasuryError> {
+    let lp_vault_id = accounts[3].account_id;
+    let pool_id = accounts[4].account_id;
+    let ledger_post = ledger::append(state, accounts, kind, lp_vault_id, pool_id, amount)?;
+    accounts[0].account.data = account_data::encode(state)?;
+    Ok(ledger_post)
+}
+
+fn output(
+    accounts: &[AccountWithMetadata],
+    call: ChainedCall,
+    ledger_post: AccountPostState,
+) -> ProgramOutput {
+    // Build post_states; the AMM moves the funds
+    let mut post_states: Vec<_> = accounts
+        .iter()
+        .map(|account| AccountPostState::new(account.account.clone()))
+        .collect();
+    *post_states.last_mut().unwrap() = ledger_post;
+
+    ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states,
+        chained_calls: vec![call],
+    }
+}
@@ -0,0 +1,95 @@
+//! Handler for MintMore — mints more of a treasury-created token into its vault.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::AccountWithMetadata;
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use token_core::TokenDefinition;
+use treasury_core::{LedgerKind, TreasuryError, TreasuryState, token_definition_pda_seed};
+
+use crate::account_data;
+use crate::auth::{require_admin, require_pda};
+use crate::ledger;
+use crate::token;
+
+/// Accounts: `[treasury_state, token_definition, vault_holding, admin,
+/// ledger_page]`. The admin must sign.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    amount: u128,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 5 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 5,
+            actual: accounts.len(),
+        });
+    }
+    if amount == 0 {
+        return Err(TreasuryError::ZeroAmount);
+    }
+
+    // Read data first to avoid borrow issues
+    let treasury_data = accounts[0].account.data.clone();
+    let token_def_data = accounts[1].account.clone();
+    let vault_data = accounts[2].account.clone();
+    let admin_data = accounts[3].account.clone();
+    let token_def_id = accounts[1].account_id;
+    let vault_id = accounts[2].account_id;
+
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    require_admin(&state, &accounts[3])?;
+
+    let index = state
+        .vault_index(&token_def_id)
+        .ok_or(TreasuryError::UnknownVault)?;
+    // Only a definition created at the vault's PDA can be authorized below
+    let token_def_seed = token_definition_pda_seed(&state.treasury_id, index as u64);
+    require_pda(&token_def_id, &accounts[0], &token_def_seed)
+        .map_err(|_| TreasuryError::NotMintable)?;
+    let vault = &mut state.vaults[index];
+    if vault.holding_id != vault_id {
+        return Err(TreasuryError::UnknownVault);
+    }
+    vault.require_active()?;
+
+    // Enforce the cap against the definition's current total supply
+    let total_supply = match TokenDefinition::try_from(&token_def_data.data) {
+        Ok(TokenDefinition::Fungible { total_supply, .. }) => total_supply,
+        _ => return Err(TreasuryError::UnexpectedAccount),
+    };
+    let new_supply = total_supply
+        .checked_add(amount)
+        .ok_or(TreasuryError::Overflow)?;
+    if vault.supply_cap.is_some_and(|cap| new_supply > cap) {
+        return Err(TreasuryError::SupplyCapExceeded);
+    }
+    vault.minted = vault.minted.checked_add(amount).ok_or(TreasuryError::Overflow)?;
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::MintMore,
+        vault_id,
+        token_def_id,
+        amount,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // The treasury authorizes its token definition PDA as minter
+    let token_def_meta = AccountWithMetadata::new(token_def_data.clone(), true, token_def_id);
+    let vault_meta = AccountWithMetadata::new(vault_data.clone(), false, vault_id);
+    let mint_call = token::mint_call(token_program_id, vec![token_def_meta, vault_meta], amount)
+        .with_pda_seeds(vec![token_def_seed]);
+
+    // Build post_states
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let token_def_post = AccountPostState::new(token_def_data);
+    let vault_post = AccountPostState::new(vault_data);
+    let admin_post = AccountPostState::new(admin_data);
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![treasury_post, token_def_post, vault_post, admin_post, ledger_post],
+        chained_calls: vec![mint_call],
+    })
+}
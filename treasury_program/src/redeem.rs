@@ -0,0 +1,116 @@
+//! Handler for Redeem — burns vault shares and pays out the proportional amount.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::AccountWithMetadata;
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use treasury_core::{
+    LedgerKind, TokenHolding, TreasuryError, TreasuryState, redemption_amount,
+    share_definition_pda_seed, vault_holding_pda_seed,
+};
+
+use crate::account_data;
+use crate::ledger;
+use crate::token::{self, pda_transfer_call};
+
+/// Accounts: `[treasury_state, vault_holding, share_definition, share_holding,
+/// recipient_holding, ledger_page]`. The share holding must be signed by its owner.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    shares: u128,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 6 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 6,
+            actual: accounts.len(),
+        });
+    }
+    if !accounts[3].is_authorized {
+        return Err(TreasuryError::Unauthorized);
+    }
+
+    // Read data first to avoid borrow issues
+    let treasury_data = accounts[0].account.data.clone();
+    let vault_data = accounts[1].account.clone();
+    let share_def_data = accounts[2].account.clone();
+    let share_holding_data = accounts[3].account.clone();
+    let recipient_data = accounts[4].account.clone();
+    let vault_id = accounts[1].account_id;
+    let share_def_id = accounts[2].account_id;
+    let share_holding_id = accounts[3].account_id;
+    let recipient_id = accounts[4].account_id;
+
+    let vault_holding =
+        TokenHolding::decode(&vault_data.data).ok_or(TreasuryError::InvalidVaultHolding)?;
+
+    // Pay out pro-rata to the vault balance and all outstanding shares
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    let vault = state
+        .vault_mut(&vault_holding.definition_id)
+        .filter(|vault| vault.holding_id == vault_id)
+        .ok_or(TreasuryError::UnknownVault)?;
+    vault.require_active()?;
+    if shares == 0 || shares > vault.share_supply {
+        return Err(TreasuryError::InvalidShareAmount);
+    }
+    let total_shares = vault.total_shares().ok_or(TreasuryError::Overflow)?;
+    let payout = redemption_amount(shares, vault_holding.balance, total_shares)?;
+    if payout == 0 {
+        return Err(TreasuryError::InvalidShareAmount);
+    }
+    vault.share_supply = vault
+        .share_supply
+        .checked_sub(shares)
+        .ok_or(TreasuryError::InvalidShareAmount)?;
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::Redeem,
+        vault_id,
+        recipient_id,
+        payout,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // Burn the shares. The share definition is marked authorized with its PDA
+    // seed so the runtime proves it is this vault's share definition.
+    let share_def_meta = AccountWithMetadata::new(share_def_data.clone(), true, share_def_id);
+    let share_holding_meta =
+        AccountWithMetadata::new(share_holding_data.clone(), true, share_holding_id);
+    let burn_call =
+        token::burn_call(token_program_id, vec![share_def_meta, share_holding_meta], shares)
+            .with_pda_seeds(vec![share_definition_pda_seed(
+                &state.treasury_id,
+                &vault_holding.definition_id,
+            )]);
+
+    // Pay out from the vault, authorized by the treasury through its PDA seed
+    let payout_call = pda_transfer_call(
+        token_program_id,
+        &accounts[1],
+        &accounts[4],
+        vault_holding_pda_seed(&state.treasury_id, &vault_holding.definition_id),
+        payout,
+    );
+
+    // Build post_states
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let vault_post = AccountPostState::new(vault_data);
+    let share_def_post = AccountPostState::new(share_def_data);
+    let share_holding_post = AccountPostState::new(share_holding_data);
+    let recipient_post = AccountPostState::new(recipient_data);
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![
+            treasury_post,
+            vault_post,
+            share_def_post,
+            share_holding_post,
+            recipient_post,
+            ledger_post,
+        ],
+        chained_calls: vec![burn_call, payout_call],
+    })
+}
@@ -0,0 +1,72 @@
+//! Handlers for AssignRole and RevokeRole — manage the role registry.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::{AccountId, AccountWithMetadata};
+use nssa_core::program::{AccountPostState, ProgramOutput};
+use treasury_core::{LedgerKind, Role, RoleAssignment, TreasuryError, TreasuryState};
+
+use crate::account_data;
+use crate::auth::require_admin;
+use crate::ledger;
+
+/// AssignRole. Accounts: `[treasury_state, admin, ledger_page]`. The admin must sign.
+/// Assigning a role the account already holds is a no-op.
+pub fn assign(
+    accounts: &mut [AccountWithMetadata],
+    role: Role,
+    account: &AccountId,
+) -> Result<ProgramOutput, TreasuryError> {
+    update(accounts, LedgerKind::AssignRole, account, |state| {
+        if !state.has_role(role, account) {
+            state.roles.push(RoleAssignment {
+                role,
+                account: *account,
+            });
+        }
+    })
+}
+
+/// RevokeRole. Accounts: `[treasury_state, admin, ledger_page]`. The admin must sign.
+/// Revoking a role the account does not hold is a no-op.
+pub fn revoke(
+    accounts: &mut [AccountWithMetadata],
+    role: Role,
+    account: &AccountId,
+) -> Result<ProgramOutput, TreasuryError> {
+    update(accounts, LedgerKind::RevokeRole, account, |state| {
+        state
+            .roles
+            .retain(|assignment| assignment.role != role || assignment.account != *account);
+    })
+}
+
+fn update(
+    accounts: &mut [AccountWithMetadata],
+    kind: LedgerKind,
+    account: &AccountId,
+    change: impl FnOnce(&mut TreasuryState),
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 3 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 3,
+            actual: accounts.len(),
+        });
+    }
+
+    let mut state = TreasuryState::try_from_slice(&accounts[0].account.data).unwrap_or_default();
+    require_admin(&state, &accounts[1])?;
+    change(&mut state);
+    let ledger_post =
+        ledger::append(&mut state, accounts, kind, AccountId::default(), *account, 0)?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let admin_post = AccountPostState::new(accounts[1].account.clone());
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![treasury_post, admin_post, ledger_post],
+        chained_calls: vec![],
+    })
+}
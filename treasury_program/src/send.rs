@@ -1,64 +1,95 @@
 //! Handler for Send — transfers tokens from treasury vault to a recipient.
 
-use nssa_core::account::AccountWithMetadata;
-use nssa_core::program::{AccountPostState, ChainedCall, InstructionData, ProgramId, ProgramOutput};
+use borsh::BorshDeserialize;
+use nssa_core::account::{AccountId, AccountWithMetadata};
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use treasury_core::{LedgerKind, TokenHolding, TreasuryError, TreasuryState, vault_holding_pda_seed};
 
-/// Token transfer instruction: [0x01 || amount (16 bytes LE)]
-fn build_transfer_instruction(amount: u128) -> InstructionData {
-    let mut instruction = vec![0u8; 17];
-    instruction[0] = 0x01; // Transfer instruction tag
-    instruction[1..17].copy_from_slice(&amount.to_le_bytes());
-    
-    instruction
-        .chunks(4)
-        .map(|chunk| {
-            let mut word = [0u8; 4];
-            word.copy_from_slice(chunk);
-            u32::from_le_bytes(word)
-        })
-        .collect()
-}
+use crate::account_data;
+use crate::auth::require_admin;
+use crate::ledger;
+use crate::token::pda_transfer_call;
 
-pub fn handle(accounts: &mut [AccountWithMetadata], amount: u128, token_program_id: &ProgramId) -> ProgramOutput {
-    if accounts.len() != 3 {
-        return ProgramOutput {
-            instruction_data: vec![],
-            pre_states: accounts.to_vec(),
-            post_states: vec![],
-            chained_calls: vec![],
-        };
+/// Accounts: `[treasury_state, vault_holding, recipient_holding, admin,
+/// ledger_page]`. The admin must sign.
+///
+/// With `private_recipient` the ledger entry records a zero counterparty, so
+/// a privacy-preserving send doesn't publish who was paid.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    amount: u128,
+    token_program_id: &ProgramId,
+    memo: Option<&str>,
+    private_recipient: bool,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 5 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 5,
+            actual: accounts.len(),
+        });
     }
 
     // Read data first to avoid borrow issues
-    let treasury_data = accounts[0].account.clone();
+    let treasury_data = accounts[0].account.data.clone();
     let vault_data = accounts[1].account.clone();
     let recipient_data = accounts[2].account.clone();
+    let admin_data = accounts[3].account.clone();
     let vault_id = accounts[1].account_id;
-    let recipient_id = accounts[2].account_id;
-
-    // Build chained call to Token program
-    let instruction_data = build_transfer_instruction(amount);
-    
-    // Provide vault and recipient as pre_states
-    let vault_meta = AccountWithMetadata::new(vault_data.clone(), true, vault_id);
-    let recipient_meta = AccountWithMetadata::new(recipient_data.clone(), false, recipient_id);
-    
-    let chained_call = ChainedCall {
-        program_id: *token_program_id,
-        instruction_data,
-        pre_states: vec![vault_meta, recipient_meta],
-        pda_seeds: vec![],
+    let recipient_id = if private_recipient {
+        AccountId::default()
+    } else {
+        accounts[2].account_id
     };
 
+    // The vault PDA seed is derived from the token definition it holds
+    let vault_holding =
+        TokenHolding::decode(&vault_data.data).ok_or(TreasuryError::InvalidVaultHolding)?;
+
+    // The vault's PDA seed authorizes the transfer, so only the admin may
+    // trigger it
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    require_admin(&state, &accounts[3])?;
+    let vault = state
+        .vault_mut(&vault_holding.definition_id)
+        .filter(|vault| vault.holding_id == vault_id)
+        .ok_or(TreasuryError::UnknownVault)?;
+    vault.require_active()?;
+    vault.sent = vault.sent.checked_add(amount).ok_or(TreasuryError::Overflow)?;
+    vault.send_count = vault
+        .send_count
+        .checked_add(1)
+        .ok_or(TreasuryError::Overflow)?;
+    let ledger_post = ledger::append_with_memo(
+        &mut state,
+        accounts,
+        LedgerKind::Send,
+        vault_id,
+        recipient_id,
+        amount,
+        memo,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // Build chained call to Token program; the vault is authorized by the
+    // treasury through its PDA seed
+    let chained_call = pda_transfer_call(
+        token_program_id,
+        &accounts[1],
+        &accounts[2],
+        vault_holding_pda_seed(&state.treasury_id, &vault_holding.definition_id),
+        amount,
+    );
+
     // Build post_states
-    let treasury_post = AccountPostState::new(treasury_data);
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
     let vault_post = AccountPostState::new(vault_data);
     let recipient_post = AccountPostState::new(recipient_data);
+    let admin_post = AccountPostState::new(admin_data);
 
-    ProgramOutput {
+    Ok(ProgramOutput {
         instruction_data: vec![],
         pre_states: accounts.to_vec(),
-        post_states: vec![treasury_post, vault_post, recipient_post],
+        post_states: vec![treasury_post, vault_post, recipient_post, admin_post, ledger_post],
         chained_calls: vec![chained_call],
-    }
+    })
 }
@@ -0,0 +1,55 @@
+//! Handler for SetAmmProgram — pins the AMM program the treasury swaps and
+//! provides liquidity through.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::{AccountId, AccountWithMetadata};
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use treasury_core::{LedgerKind, TreasuryError, TreasuryState};
+
+use crate::account_data;
+use crate::auth::require_admin;
+use crate::ledger;
+
+/// Accounts: `[treasury_state, admin, ledger_page]`. The admin must sign.
+///
+/// Replaces any AMM program set earlier.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    amm_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 3 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 3,
+            actual: accounts.len(),
+        });
+    }
+
+    // Read data first to avoid borrow issues
+    let treasury_data = accounts[0].account.data.clone();
+    let admin_data = accounts[1].account.clone();
+    let admin_id = accounts[1].account_id;
+
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    require_admin(&state, &accounts[1])?;
+    state.amm_program_id = Some(*amm_program_id);
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::SetAmmProgram,
+        AccountId::default(),
+        admin_id,
+        0,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // Build post_states
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let admin_post = AccountPostState::new(admin_data);
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![treasury_post, admin_post, ledger_post],
+        chained_calls: vec![],
+    })
+}
@@ -0,0 +1,75 @@
+//! Handler for SetMetadata — records the treasury's name, description URI and
+//! tags in the metadata PDA.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::{Account, AccountId, AccountWithMetadata};
+use nssa_core::program::{AccountPostState, ProgramOutput};
+use treasury_core::{LedgerKind, TreasuryError, TreasuryMetadata, TreasuryState, metadata_pda_seed};
+
+use crate::account_data;
+use crate::auth::{require_admin, require_pda};
+use crate::ledger;
+
+/// Accounts: `[treasury_state, metadata, admin, ledger_page]`. The metadata
+/// account must be the treasury's metadata PDA; the admin must sign.
+///
+/// The first call claims the PDA; later calls replace its contents.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    name: &str,
+    uri: &str,
+    tags: &[String],
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 4 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 4,
+            actual: accounts.len(),
+        });
+    }
+
+    // Read data first to avoid borrow issues
+    let treasury_data = accounts[0].account.data.clone();
+    let metadata_data = accounts[1].account.clone();
+    let admin_data = accounts[2].account.clone();
+    let metadata_id = accounts[1].account_id;
+
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    require_admin(&state, &accounts[2])?;
+    require_pda(&metadata_id, &accounts[0], &metadata_pda_seed(&state.treasury_id))?;
+    let metadata = TreasuryMetadata {
+        name: name.to_string(),
+        uri: uri.to_string(),
+        tags: tags.to_vec(),
+    };
+    metadata.validate()?;
+
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::SetMetadata,
+        AccountId::default(),
+        metadata_id,
+        0,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    let is_new = metadata_data == Account::default();
+    let mut metadata_account = metadata_data;
+    metadata_account.data = account_data::encode(&metadata)?;
+
+    // Build post_states. The treasury claims the metadata PDA on first use.
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let metadata_post = if is_new {
+        AccountPostState::new_claimed(metadata_account)
+    } else {
+        AccountPostState::new(metadata_account)
+    };
+    let admin_post = AccountPostState::new(admin_data);
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![treasury_post, metadata_post, admin_post, ledger_post],
+        chained_calls: vec![],
+    })
+}
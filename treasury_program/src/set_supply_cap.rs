@@ -0,0 +1,65 @@
+//! Handler for SetSupplyCap — sets or clears a vault's supply cap.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::AccountWithMetadata;
+use nssa_core::program::{AccountPostState, ProgramOutput};
+use treasury_core::{LedgerKind, TokenHolding, TreasuryError, TreasuryState};
+
+use crate::account_data;
+use crate::auth::require_admin;
+use crate::ledger;
+
+/// Accounts: `[treasury_state, vault_holding, admin, ledger_page]`. The admin
+/// must sign. The ledger records a cleared cap as zero.
+///
+/// A cap below the current supply is allowed; it just blocks further mints.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    cap: Option<u128>,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 4 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 4,
+            actual: accounts.len(),
+        });
+    }
+
+    // Read data first to avoid borrow issues
+    let treasury_data = accounts[0].account.data.clone();
+    let vault_data = accounts[1].account.clone();
+    let admin_data = accounts[2].account.clone();
+    let vault_id = accounts[1].account_id;
+
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    require_admin(&state, &accounts[2])?;
+
+    let vault_holding =
+        TokenHolding::decode(&vault_data.data).ok_or(TreasuryError::InvalidVaultHolding)?;
+    let vault = state
+        .vault_mut(&vault_holding.definition_id)
+        .filter(|vault| vault.holding_id == vault_id)
+        .ok_or(TreasuryError::UnknownVault)?;
+    vault.supply_cap = cap;
+    let admin_id = accounts[2].account_id;
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::SetSupplyCap,
+        vault_id,
+        admin_id,
+        cap.unwrap_or(0),
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // Build post_states
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let vault_post = AccountPostState::new(vault_data);
+    let admin_post = AccountPostState::new(admin_data);
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![treasury_post, vault_post, admin_post, ledger_post],
+        chained_calls: vec![],
+    })
+}
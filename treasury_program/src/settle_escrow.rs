@@ -0,0 +1,200 @@
+//! Handlers for Release, Refund and Reclaim — pay out an open escrow.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::AccountWithMetadata;
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use treasury_core::{
+    Escrow, EscrowStatus, LedgerKind, TreasuryError, TreasuryState, clock_account_id,
+    compute_treasury_state_pda, escrow_holding_pda_seed, escrow_pda_seed, read_clock,
+};
+
+use crate::account_data;
+use crate::auth::{require_admin, require_pda};
+use crate::ledger;
+use crate::token::pda_transfer_call;
+
+/// Release. Accounts: `[treasury_state, escrow, escrow_holding,
+/// beneficiary_holding, arbiter, ledger_page]`. The arbiter must sign.
+pub fn release(
+    accounts: &mut [AccountWithMetadata],
+    escrow: u64,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    check_account_count(accounts, 6)?;
+    let (state, record) = open_escrow(accounts, escrow)?;
+    require_arbiter(&record, &accounts[4])?;
+    if accounts[3].account_id != record.beneficiary {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    settle(
+        accounts,
+        state,
+        escrow,
+        record,
+        EscrowStatus::Released,
+        LedgerKind::Release,
+        token_program_id,
+    )
+}
+
+/// Refund. Accounts: `[treasury_state, escrow, escrow_holding, vault_holding,
+/// arbiter, ledger_page]`. The arbiter must sign.
+pub fn refund(
+    accounts: &mut [AccountWithMetadata],
+    escrow: u64,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    check_account_count(accounts, 6)?;
+    let (state, record) = open_escrow(accounts, escrow)?;
+    require_arbiter(&record, &accounts[4])?;
+    if accounts[3].account_id != record.vault_id {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    require_vault_active(&state, &record)?;
+    settle(
+        accounts,
+        state,
+        escrow,
+        record,
+        EscrowStatus::Refunded,
+        LedgerKind::Refund,
+        token_program_id,
+    )
+}
+
+/// Reclaim. Accounts: `[treasury_state, escrow, escrow_holding,
+/// vault_holding, admin, clock, ledger_page]`. The admin must sign.
+pub fn reclaim(
+    accounts: &mut [AccountWithMetadata],
+    escrow: u64,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    check_account_count(accounts, 7)?;
+    let (state, record) = open_escrow(accounts, escrow)?;
+    require_admin(&state, &accounts[4])?;
+    if accounts[5].account_id != clock_account_id() {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    let now = read_clock(&accounts[5].account.data).ok_or(TreasuryError::InvalidClock)?;
+    if now <= record.deadline {
+        return Err(TreasuryError::DeadlineNotReached);
+    }
+    if accounts[3].account_id != record.vault_id {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    require_vault_active(&state, &record)?;
+    settle(
+        accounts,
+        state,
+        escrow,
+        record,
+        EscrowStatus::Reclaimed,
+        LedgerKind::Reclaim,
+        token_program_id,
+    )
+}
+
+fn check_account_count(
+    accounts: &[AccountWithMetadata],
+    expected: usize,
+) -> Result<(), TreasuryError> {
+    if accounts.len() != expected {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected,
+            actual: accounts.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Decode the treasury state and the escrow, checking the escrow is the PDA
+/// for `escrow` and still open.
+fn open_escrow(
+    accounts: &[AccountWithMetadata],
+    escrow: u64,
+) -> Result<(TreasuryState, Escrow), TreasuryError> {
+    let escrow_account = &accounts[1];
+    let state = TreasuryState::try_from_slice(&accounts[0].account.data).unwrap_or_default();
+    let treasury = &state.treasury_id;
+    require_pda(
+        &escrow_account.account_id,
+        escrow_account,
+        &escrow_pda_seed(treasury, escrow),
+    )?;
+    let record = Escrow::try_from_slice(&escrow_account.account.data)
+        .map_err(|_| TreasuryError::UnexpectedAccount)?;
+    if record.status != EscrowStatus::Open {
+        return Err(TreasuryError::EscrowClosed);
+    }
+
+    // The escrow is written, so its owner is this program; use it to check
+    // the treasury state is ours too.
+    let owner = &escrow_account.account.program_owner;
+    if accounts[0].account_id != compute_treasury_state_pda(owner, treasury) {
+        return Err(TreasuryError::UnexpectedAccount);
+    }
+    Ok((state, record))
+}
+
+fn require_arbiter(record: &Escrow, signer: &AccountWithMetadata) -> Result<(), TreasuryError> {
+    if signer.account_id != record.arbiter || !signer.is_authorized {
+        return Err(TreasuryError::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Refunds and reclaims pay back into the vault, so it must not be frozen.
+fn require_vault_active(state: &TreasuryState, record: &Escrow) -> Result<(), TreasuryError> {
+    state
+        .vault(&record.token_definition_id)
+        .ok_or(TreasuryError::UnknownVault)?
+        .require_active()
+}
+
+/// Close the escrow with `status`, log the payout as `kind`, and pay its funds
+/// from the escrow holding (`accounts[2]`) to the destination (`accounts[3]`).
+fn settle(
+    accounts: &mut [AccountWithMetadata],
+    mut state: TreasuryState,
+    escrow: u64,
+    mut record: Escrow,
+    status: EscrowStatus,
+    kind: LedgerKind,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    record.status = status;
+    accounts[1].account.data = account_data::encode(&record)?;
+
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        kind,
+        record.vault_id,
+        record.beneficiary,
+        record.amount,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // Pay out from the escrow holding, authorized through its PDA seed
+    let payout_call = pda_transfer_call(
+        token_program_id,
+        &accounts[2],
+        &accounts[3],
+        escrow_holding_pda_seed(&state.treasury_id, escrow),
+        record.amount,
+    );
+
+    // Build post_states; the ledger page may be claimed
+    let mut post_states: Vec<_> = accounts
+        .iter()
+        .map(|account| AccountPostState::new(account.account.clone()))
+        .collect();
+    *post_states.last_mut().unwrap() = ledger_post;
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states,
+        chained_calls: vec![payout_call],
+    })
+}
@@ -0,0 +1,184 @@
+//! Handlers for SetSplit and DepositSplit — divide a token's deposits between
+//! labeled vaults.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::AccountWithMetadata;
+use nssa_core::program::{AccountPostState, ProgramId, ProgramOutput};
+use treasury_core::{
+    LedgerKind, SplitConfig, TokenHolding, TreasuryError, TreasuryState, labeled_vault_pda_seed,
+};
+
+use crate::account_data;
+use crate::auth::{require_admin, require_pda};
+use crate::ledger;
+use crate::token;
+
+/// SetSplit. Accounts: `[treasury_state, vault_holding, admin, ledger_page]`.
+/// The admin must sign. The ledger records the number of targets, or zero
+/// when the split is cleared.
+pub fn set(
+    accounts: &mut [AccountWithMetadata],
+    split: Option<&SplitConfig>,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 4 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 4,
+            actual: accounts.len(),
+        });
+    }
+
+    // Read data first to avoid borrow issues
+    let treasury_data = accounts[0].account.data.clone();
+    let vault_data = accounts[1].account.clone();
+    let admin_data = accounts[2].account.clone();
+    let vault_id = accounts[1].account_id;
+    let admin_id = accounts[2].account_id;
+
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    require_admin(&state, &accounts[2])?;
+    if let Some(split) = split {
+        split.validate()?;
+    }
+
+    let vault_holding =
+        TokenHolding::decode(&vault_data.data).ok_or(TreasuryError::InvalidVaultHolding)?;
+    let vault = state
+        .vault_mut(&vault_holding.definition_id)
+        .filter(|vault| vault.holding_id == vault_id)
+        .ok_or(TreasuryError::UnknownVault)?;
+    vault.split = split.cloned();
+    let targets = split.map_or(0, |split| split.targets.len());
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::SetSplit,
+        vault_id,
+        admin_id,
+        targets as u128,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // Build post_states
+    let treasury_post = AccountPostState::new(accounts[0].account.clone());
+    let vault_post = AccountPostState::new(vault_data);
+    let admin_post = AccountPostState::new(admin_data);
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states: vec![treasury_post, vault_post, admin_post, ledger_post],
+        chained_calls: vec![],
+    })
+}
+
+/// DepositSplit. Accounts: `[treasury_state, sender_holding, target_vault...,
+/// ledger_page]`, with one labeled vault per split target, in target order.
+/// The sender must sign.
+///
+/// The split is the one set for the sender's token. Each target gets its
+/// share rounded down and the dust target also gets the remainder; targets
+/// whose part is zero are skipped.
+pub fn deposit(
+    accounts: &mut [AccountWithMetadata],
+    amount: u128,
+    token_program_id: &ProgramId,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() < 3 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 3,
+            actual: accounts.len(),
+        });
+    }
+
+    // Read data first to avoid borrow issues
+    let treasury_data = accounts[0].account.data.clone();
+    let sender_data = accounts[1].account.clone();
+    let sender_id = accounts[1].account_id;
+    let sender_authorized = accounts[1].is_authorized;
+
+    let sender_holding =
+        TokenHolding::decode(&sender_data.data).ok_or(TreasuryError::UnexpectedAccount)?;
+    let definition_id = sender_holding.definition_id;
+    let mut state = TreasuryState::try_from_slice(&treasury_data).unwrap_or_default();
+    let vault = state.vault(&definition_id).ok_or(TreasuryError::UnknownVault)?;
+    vault.require_active()?;
+    let vault_id = vault.holding_id;
+    let split = vault.split.clone().ok_or(TreasuryError::InvalidSplit)?;
+    // treasury_state, sender_holding, one vault per target, ledger_page
+    let expected = split.targets.len().checked_add(3).ok_or(TreasuryError::InvalidSplit)?;
+    if accounts.len() != expected {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected,
+            actual: accounts.len(),
+        });
+    }
+    if amount == 0 {
+        return Err(TreasuryError::ZeroAmount);
+    }
+    if !sender_authorized {
+        return Err(TreasuryError::Unauthorized);
+    }
+    for (target, account) in split.targets.iter().zip(&accounts[2..]) {
+        let seed = labeled_vault_pda_seed(&state.treasury_id, &definition_id, &target.label);
+        require_pda(&account.account_id, &accounts[0], &seed)?;
+    }
+
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::DepositSplit,
+        vault_id,
+        sender_id,
+        amount,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // One transfer per target. Each call sees the sender's balance as the
+    // previous transfers left it. The targets are authorized so the Token
+    // program can claim them on their first deposit.
+    let mut sender_balance = sender_holding.balance;
+    let mut chained_calls = Vec::new();
+    for ((target, part), account) in split
+        .targets
+        .iter()
+        .zip(split.amounts(amount)?)
+        .zip(&accounts[2..])
+    {
+        if part == 0 {
+            continue;
+        }
+        let mut sender = sender_data.clone();
+        sender.data = TokenHolding {
+            balance: sender_balance,
+            ..sender_holding
+        }
+        .encode();
+        sender_balance = sender_balance
+            .checked_sub(part)
+            .ok_or(TreasuryError::InsufficientBalance)?;
+
+        let sender_meta = AccountWithMetadata::new(sender, true, sender_id);
+        let target_meta =
+            AccountWithMetadata::new(account.account.clone(), true, account.account_id);
+        let seed = labeled_vault_pda_seed(&state.treasury_id, &definition_id, &target.label);
+        chained_calls.push(
+            token::transfer_call(token_program_id, vec![sender_meta, target_meta], part)
+                .with_pda_seeds(vec![seed]),
+        );
+    }
+
+    // Build post_states
+    let last = expected.checked_sub(1).ok_or(TreasuryError::InvalidSplit)?;
+    let mut post_states: Vec<AccountPostState> = accounts[..last]
+        .iter()
+        .map(|account| AccountPostState::new(account.account.clone()))
+        .collect();
+    post_states.push(ledger_post);
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states,
+        chained_calls,
+    })
+}
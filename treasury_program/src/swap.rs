@@ -0,0 +1,115 @@
+//! Handler for Swap — swaps between two treasury vaults through an AMM pool.
+
+use borsh::BorshDeserialize;
+use nssa_core::account::AccountWithMetadata;
+use nssa_core::program::{AccountPostState, ProgramOutput};
+use treasury_core::{
+    LedgerKind, TokenHolding, TreasuryError, TreasuryState, vault_holding_pda_seed,
+};
+
+use crate::account_data;
+use crate::amm;
+use crate::auth::{require_admin, require_state_pda};
+use crate::ledger;
+
+/// Accounts: `[treasury_state, from_vault, to_vault, pool, pool_vault_a,
+/// pool_vault_b, admin, ledger_page]`. The admin must sign, and the pool
+/// must belong to the AMM program set with SetAmmProgram.
+pub fn handle(
+    accounts: &mut [AccountWithMetadata],
+    amount_in: u128,
+    min_amount_out: u128,
+) -> Result<ProgramOutput, TreasuryError> {
+    if accounts.len() != 8 {
+        return Err(TreasuryError::InvalidAccountCount {
+            expected: 8,
+            actual: accounts.len(),
+        });
+    }
+    if amount_in == 0 {
+        return Err(TreasuryError::ZeroAmount);
+    }
+    // The AMM rejects the swap if it would pay out less than this
+    if min_amount_out == 0 {
+        return Err(TreasuryError::InvalidSlippage);
+    }
+
+    let mut state = TreasuryState::try_from_slice(&accounts[0].account.data).unwrap_or_default();
+    require_admin(&state, &accounts[6])?;
+    let amm_program_id = amm::pinned_program(&state, &accounts[3])?;
+
+    let from_def = TokenHolding::decode(&accounts[1].account.data)
+        .ok_or(TreasuryError::InvalidVaultHolding)?
+        .definition_id;
+    let def_a = TokenHolding::decode(&accounts[4].account.data)
+        .ok_or(TreasuryError::UnexpectedAccount)?
+        .definition_id;
+    let def_b = TokenHolding::decode(&accounts[5].account.data)
+        .ok_or(TreasuryError::UnexpectedAccount)?
+        .definition_id;
+    let (to_def, from_is_a) = if from_def == def_a {
+        (def_b, true)
+    } else if from_def == def_b {
+        (def_a, false)
+    } else {
+        return Err(TreasuryError::UnexpectedAccount);
+    };
+
+    // Both sides must be registered vaults of this treasury
+    let from_vault_id = accounts[1].account_id;
+    let to_vault_id = accounts[2].account_id;
+    for (def, vault_id) in [(from_def, from_vault_id), (to_def, to_vault_id)] {
+        state
+            .vault(&def)
+            .filter(|vault| vault.holding_id == vault_id)
+            .ok_or(TreasuryError::UnknownVault)?
+            .require_active()?;
+    }
+    let from_seed = vault_holding_pda_seed(&state.treasury_id, &from_def);
+    require_state_pda(&accounts[0], &state.treasury_id, &from_vault_id, &from_seed)?;
+
+    let pool_id = accounts[3].account_id;
+    let ledger_post = ledger::append(
+        &mut state,
+        accounts,
+        LedgerKind::Swap,
+        from_vault_id,
+        pool_id,
+        amount_in,
+    )?;
+    accounts[0].account.data = account_data::encode(&state)?;
+
+    // The source vault authorizes the swap through its PDA seed; the
+    // destination vault receives the output
+    let from_meta = AccountWithMetadata::new(accounts[1].account.clone(), true, from_vault_id);
+    let to_meta = AccountWithMetadata::new(accounts[2].account.clone(), false, to_vault_id);
+    let (holding_a, holding_b) = if from_is_a {
+        (from_meta, to_meta)
+    } else {
+        (to_meta, from_meta)
+    };
+    let pool_accounts = vec![
+        accounts[3].clone(),
+        accounts[4].clone(),
+        accounts[5].clone(),
+        holding_a,
+        holding_b,
+    ];
+    let swap_call =
+        amm::swap_call(&amm_program_id, pool_accounts, amount_in, min_amount_out, from_def)
+            .with_pda_seeds(vec![from_seed]);
+
+    // Build post_states; the AMM moves the funds
+    let mut post_states: Vec<_> = accounts
+        .iter()
+        .map(|account| AccountPostState::new(account.account.clone()))
+        .collect();
+    *post_states.last_mut().unwrap() = ledger_post;
+
+    Ok(ProgramOutput {
+        instruction_data: vec![],
+        pre_states: accounts.to_vec(),
+        post_states,
+        chained_calls: vec![swap_call],
+    })
+}
@@ -0,0 +1,88 @@
+//! Chained calls to the Token program shared by the handlers.
+//!
+//! Instructions are the Token program's own `token_core::Instruction`, which
+//! `ChainedCall::new` serializes the way the Token program reads it.
+
+use nssa_core::account::AccountWithMetadata;
+use nssa_core::program::{ChainedCall, PdaSeed, ProgramId};
+use treasury_core::{TreasuryError, validate_token_name};
+
+/// Token `NewFungibleDefinition`: create `definition` and mint `total_supply`
+/// into `holding`. Accounts: `[definition, holding]`.
+///
+/// Fails if the name is too long, rather than truncating it.
+pub fn new_definition_call(
+    token_program_id: &ProgramId,
+    pre_states: Vec<AccountWithMetadata>,
+    name: &str,
+    total_supply: u128,
+) -> Result<ChainedCall, TreasuryError> {
+    validate_token_name(name)?;
+    Ok(ChainedCall::new(
+        *token_program_id,
+        pre_states,
+        &token_core::Instruction::NewFungibleDefinition {
+            name: name.to_string(),
+            total_supply,
+        },
+    ))
+}
+
+/// Token `Transfer`. Accounts: `[sender (authorized), recipient]`.
+pub fn transfer_call(
+    token_program_id: &ProgramId,
+    pre_states: Vec<AccountWithMetadata>,
+    amount: u128,
+) -> ChainedCall {
+    ChainedCall::new(
+        *token_program_id,
+        pre_states,
+        &token_core::Instruction::Transfer {
+            amount_to_transfer: amount,
+        },
+    )
+}
+
+/// Token `Burn`. Accounts: `[definition, holding (authorized)]`.
+pub fn burn_call(
+    token_program_id: &ProgramId,
+    pre_states: Vec<AccountWithMetadata>,
+    amount: u128,
+) -> ChainedCall {
+    ChainedCall::new(
+        *token_program_id,
+        pre_states,
+        &token_core::Instruction::Burn {
+            amount_to_burn: amount,
+        },
+    )
+}
+
+/// Token `Mint`. Accounts: `[definition (authorized), holding]`.
+pub fn mint_call(
+    token_program_id: &ProgramId,
+    pre_states: Vec<AccountWithMetadata>,
+    amount: u128,
+) -> ChainedCall {
+    ChainedCall::new(
+        *token_program_id,
+        pre_states,
+        &token_core::Instruction::Mint {
+            amount_to_mint: amount,
+        },
+    )
+}
+
+/// Chained Token transfer out of a treasury PDA holding. The holding is
+/// marked authorized and the runtime checks it against `seed`.
+pub fn pda_transfer_call(
+    token_program_id: &ProgramId,
+    from: &AccountWithMetadata,
+    to: &AccountWithMetadata,
+    seed: PdaSeed,
+    amount: u128,
+) -> ChainedCall {
+    let from_meta = AccountWithMetadata::new(from.account.clone(), true, from.account_id);
+    let to_meta = AccountWithMetadata::new(to.account.clone(), false, to.account_id);
+    transfer_call(token_program_id, vec![from_meta, to_meta], amount).with_pda_seeds(vec![seed])
+}